@@ -1,80 +1,312 @@
 use std::env;
 use std::fs;
 
-fn reset_spelled_numbers(spellings: &mut Vec<(&'static str, u32, usize)>) {
-    for spell in &mut *spellings {
-        spell.2 = 0;
-    }
+const ENGLISH: &str = "one=1,two=2,three=3,four=4,five=5,six=6,seven=7,eight=8,nine=9";
+const GERMAN: &str = "eins=1,zwei=2,drei=3,vier=4,fünf=5,sechs=6,sieben=7,acht=8,neun=9";
+
+struct Spelling {
+    token: String,
+    value: u32,
 }
 
-fn main() {
-    // setup spelled numbers
-    let mut spellings: Vec<(&'static str, u32, usize)> = vec![
-        ("one", 1, 0),
-        ("two", 2, 0),
-        ("three", 3, 0),
-        ("four", 4, 0),
-        ("five", 5, 0),
-        ("six", 6, 0),
-        ("seven", 7, 0),
-        ("eight", 8, 0),
-        ("nine", 9, 0)
-        ];
-
-    // setup input file
-    let args: Vec<String> = env::args().collect();
-    let path = &args[1];
+enum Format {
+    Table,
+    Csv,
+}
 
-    let contents = fs::read_to_string(path)
-        .expect("Should have been able to read the file");
+struct Options {
+    path: String,
+    spellings: Vec<Spelling>,
+    format: Format,
+}
 
-    let bytes = contents.as_bytes();
+fn parse_spelling(entry: &str) -> Result<Spelling, String> {
+    let (token, value) = entry
+        .rsplit_once('=')
+        .ok_or(format!("Expected token=value, got '{entry}'"))?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(format!("Empty token in '{entry}'"));
+    }
+    let value = value
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| format!("Invalid value in '{entry}': {e}"))?;
+    Ok(Spelling {
+        token: token.to_string(),
+        value,
+    })
+}
 
-    // setup cache values
-    let mut first_digit:u32 = 0;
-    let mut first_digits:u32 = 0;
-    let mut last_digits:u32 = 0;
+// entries are separated by ',' or newlines, lines starting with '#' are comments
+fn parse_vocabulary(text: &str) -> Result<Vec<Spelling>, String> {
+    let mut spellings = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for entry in line.split(',') {
+            let entry = entry.trim();
+            if !entry.is_empty() {
+                spellings.push(parse_spelling(entry)?);
+            }
+        }
+    }
+    if spellings.is_empty() {
+        return Err("Vocabulary does not contain any spellings".to_string());
+    }
+    Ok(spellings)
+}
 
-    let mut num_cache = 0xf;
+fn preset_vocabulary(lang: &str) -> Result<Vec<Spelling>, String> {
+    match lang {
+        "en" => parse_vocabulary(ENGLISH),
+        "de" => parse_vocabulary(GERMAN),
+        _ => Err(format!("Unknown language '{lang}', expected en or de")),
+    }
+}
 
-    // find numbers
-    for (i, &item) in bytes.iter().enumerate() {
-        if item == b'\n' {
-            if num_cache == 0xf {
-                panic!("Not enough Numbers in line {i}");
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut path = None;
+    let mut spellings = None;
+    let mut format = Format::Table;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "--vocab" => {
+                let file = value("--vocab")?;
+                let text = fs::read_to_string(file)
+                    .map_err(|e| format!("Could not read vocabulary {file}: {e}"))?;
+                spellings = Some(parse_vocabulary(&text)?);
             }
-            println!("Line {i}: {},{}", first_digit, u32::from(num_cache));
-            last_digits += u32::from(num_cache);
-            num_cache = 0xf;
-            reset_spelled_numbers(&mut spellings);
-        } else if item >= b'0' && item <= b'9' {
-            if num_cache == 0xf {
-                first_digits += u32::from(item - b'0');
-                first_digit = u32::from(item - b'0');
-            }
-            num_cache = u32::from(item - b'0');
-            reset_spelled_numbers(&mut spellings);
-        } else {
-            for spelling_item in &mut spellings {
-                if item == spelling_item.0.as_bytes()[spelling_item.2] {
-                    spelling_item.2 += 1;
-                    if spelling_item.2 == spelling_item.0.len() {
-                        if num_cache == 0xf {
-                            first_digit = spelling_item.1;
-                            first_digits += spelling_item.1;
-                        }
-                        num_cache = spelling_item.1;
-                        spelling_item.2 = 0;
-                    }
-                } else {
-                    if item == spelling_item.0.as_bytes()[0] {
-                        spelling_item.2 = 1;
-                    } else {
-                        spelling_item.2 = 0;
+            "--words" => spellings = Some(parse_vocabulary(value("--words")?)?),
+            "--lang" => spellings = Some(preset_vocabulary(value("--lang")?)?),
+            "--format" => {
+                format = match value("--format")?.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    other => {
+                        return Err(format!("Unknown format '{other}', expected table or csv"))
                     }
                 }
             }
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{arg}'")),
         }
     }
-    println!("Calculated Result as {}", first_digits * 10 + last_digits);
+    Ok(Options {
+        path: path.ok_or(
+            "Usage: aoc_1 <input> [--lang en|de] [--vocab <file>] [--words <token=value,...>] [--format table|csv]",
+        )?,
+        spellings: match spellings {
+            Some(spellings) => spellings,
+            None => preset_vocabulary("en")?,
+        },
+        format,
+    })
+}
+
+struct Match {
+    value: u32,
+    start: usize,
+    end: usize,
+}
+
+struct LineReport<'a> {
+    number: usize,
+    text: &'a str,
+    digits: Option<(Match, Match)>,
+    spelled: Option<(Match, Match)>,
+}
+
+impl LineReport<'_> {
+    fn digits_value(&self) -> Option<u128> {
+        self.digits
+            .as_ref()
+            .map(|(first, last)| combine(first.value, last.value))
+    }
+
+    fn spelled_value(&self) -> Option<u128> {
+        self.spelled
+            .as_ref()
+            .map(|(first, last)| combine(first.value, last.value))
+    }
+
+    fn span(&self, m: &Match) -> String {
+        format!("{}..{}:{}", m.start, m.end, &self.text[m.start..m.end])
+    }
+}
+
+// checks for a digit or spelled token starting at pos, preferring the longest token
+fn match_at(line: &str, pos: usize, spellings: &[Spelling]) -> Option<Match> {
+    let rest = &line[pos..];
+    if let Some(digit) = rest.chars().next().and_then(|c| c.to_digit(10)) {
+        return Some(Match {
+            value: digit,
+            start: pos,
+            end: pos + 1,
+        });
+    }
+    spellings
+        .iter()
+        .filter(|spelling| rest.starts_with(spelling.token.as_str()))
+        .max_by_key(|spelling| spelling.token.len())
+        .map(|spelling| Match {
+            value: spelling.value,
+            start: pos,
+            end: pos + spelling.token.len(),
+        })
+}
+
+fn find_first_last(line: &str, spellings: &[Spelling]) -> Option<(Match, Match)> {
+    let first = line
+        .char_indices()
+        .find_map(|(pos, _)| match_at(line, pos, spellings))?;
+    let last = line
+        .char_indices()
+        .rev()
+        .find_map(|(pos, _)| match_at(line, pos, spellings))?;
+    Some((first, last))
+}
+
+// joins the decimal representations, so values beyond 9 keep all of their digits,
+// two u32 values need at most 20 digits which always fits into u128
+fn combine(first: u32, last: u32) -> u128 {
+    let mut shift: u128 = 10;
+    while shift <= u128::from(last) {
+        shift *= 10;
+    }
+    u128::from(first) * shift + u128::from(last)
+}
+
+fn analyze_lines<'a>(contents: &'a str, spellings: &[Spelling]) -> Vec<LineReport<'a>> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| LineReport {
+            number: i + 1,
+            text: line,
+            digits: find_first_last(line, &[]),
+            spelled: find_first_last(line, spellings),
+        })
+        .collect()
+}
+
+// columns: first, last, value, first span, last span
+fn report_columns(report: &LineReport, pair: &Option<(Match, Match)>) -> [String; 5] {
+    match pair {
+        Some((first, last)) => [
+            first.value.to_string(),
+            last.value.to_string(),
+            combine(first.value, last.value).to_string(),
+            report.span(first),
+            report.span(last),
+        ],
+        None => ["-", "-", "-", "-", "-"].map(String::from),
+    }
+}
+
+fn print_table(reports: &[LineReport]) {
+    println!(
+        "{:>5} | {:>5} {:>5} {:>6} {:<12} {:<12} | {:>6} {:>6} {:>8} {:<20} {:<20}",
+        "Line", "D1", "DL", "D", "D1 span", "DL span", "S1", "SL", "S", "S1 span", "SL span"
+    );
+    for report in reports {
+        let [d_first, d_last, d_value, d_first_span, d_last_span] =
+            report_columns(report, &report.digits);
+        let [s_first, s_last, s_value, s_first_span, s_last_span] =
+            report_columns(report, &report.spelled);
+        println!(
+            "{:>5} | {:>5} {:>5} {:>6} {:<12} {:<12} | {:>6} {:>6} {:>8} {:<20} {:<20}",
+            report.number,
+            d_first,
+            d_last,
+            d_value,
+            d_first_span,
+            d_last_span,
+            s_first,
+            s_last,
+            s_value,
+            s_first_span,
+            s_last_span
+        );
+    }
+}
+
+fn print_csv(reports: &[LineReport]) {
+    println!("line,digits_first,digits_last,digits_value,digits_first_span,digits_last_span,spelled_first,spelled_last,spelled_value,spelled_first_span,spelled_last_span");
+    for report in reports {
+        let digits = report_columns(report, &report.digits);
+        let spelled = report_columns(report, &report.spelled);
+        println!(
+            "{},{},{}",
+            report.number,
+            digits.join(","),
+            spelled.join(",")
+        );
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
+
+    let contents =
+        fs::read_to_string(&options.path).expect("Should have been able to read the file");
+
+    let reports = analyze_lines(&contents, &options.spellings);
+    // a sum becomes None at the first line it no longer fits
+    let mut digits_sum: Option<u128> = Some(0);
+    let mut spelled_sum: Option<u128> = Some(0);
+    let mut overflows: Vec<String> = Vec::new();
+    for report in &reports {
+        let digits = digits_sum.and_then(|sum| sum.checked_add(report.digits_value().unwrap_or(0)));
+        if digits.is_none() && digits_sum.is_some() {
+            overflows.push(format!(
+                "Digits only sum overflowed in line {}",
+                report.number
+            ));
+        }
+        digits_sum = digits;
+        let spelled =
+            spelled_sum.and_then(|sum| sum.checked_add(report.spelled_value().unwrap_or(0)));
+        if spelled.is_none() && spelled_sum.is_some() {
+            overflows.push(format!("Spelled sum overflowed in line {}", report.number));
+        }
+        spelled_sum = spelled;
+    }
+
+    match options.format {
+        Format::Table => print_table(&reports),
+        Format::Csv => print_csv(&reports),
+    }
+    // notes and totals follow the rows, on stderr when the rows are csv
+    let note = |line: String| match options.format {
+        Format::Table => println!("{line}"),
+        Format::Csv => eprintln!("{line}"),
+    };
+    for report in &reports {
+        if report.spelled.is_none() {
+            note(format!("Not enough Numbers in line {}", report.number));
+        } else if report.digits.is_none() {
+            note(format!(
+                "No digits in line {}, counted as 0 for digits only",
+                report.number
+            ));
+        }
+    }
+    for overflow in overflows {
+        note(overflow);
+    }
+    let show = |sum: Option<u128>| sum.map_or("overflow".to_string(), |sum| sum.to_string());
+    note(format!("Digits only result: {}", show(digits_sum)));
+    note(format!("Spelled result: {}", show(spelled_sum)));
 }
@@ -1,141 +1,194 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 
+use pest::iterators::Pair;
+use pest::iterators::Pairs;
+use pest::Parser;
+use pest_derive::Parser;
+
+#[derive(Parser)]
+#[grammar = "grammar.pest"]
+struct MyParser;
+
+type Draw = Vec<(String, u32)>;
+
 struct Game {
     game_id: u32,
-    max_red: u32,
-    max_green: u32,
-    max_blue: u32,
+    draws: Vec<Draw>,
 }
-enum Color {
-    Red,
-    Green,
-    Blue,
+
+struct Violation {
+    draw: usize,
+    color: String,
+    num: u32,
 }
 
-fn parse_color(slice: &[u8]) -> Color {
-    if slice[0] == b'r' {
-        return Color::Red;
-    } else if slice[0] == b'g' {
-        return Color::Green;
-    } else if slice[0] == b'b' {
-        return Color::Blue;
+impl Game {
+    // for each color the fewest cubes needed and the first draw that forced that count
+    fn minimal_bag(&self) -> BTreeMap<String, (u32, usize)> {
+        let mut minimal: BTreeMap<String, (u32, usize)> = BTreeMap::new();
+        for (i, draw) in self.draws.iter().enumerate() {
+            for (color, num) in draw {
+                let entry = minimal.entry(color.clone()).or_insert((0, i));
+                if *num > entry.0 {
+                    *entry = (*num, i);
+                }
+            }
+        }
+        minimal
     }
-    println!("Not a color, returning red");
-    return Color::Red;
+
+    // colors missing from the bag hold no cubes, so drawing one makes the game impossible
+    fn first_violation(&self, bag: &Bag) -> Option<Violation> {
+        for (i, draw) in self.draws.iter().enumerate() {
+            for (color, num) in draw {
+                if bag.get(color).is_none_or(|limit| num > limit) {
+                    return Some(Violation {
+                        draw: i,
+                        color: color.clone(),
+                        num: *num,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+type Bag = BTreeMap<String, u32>;
+
+const DEFAULT_BAG: &str = "red=12,green=13,blue=14";
+
+fn parse_bag(text: &str) -> Result<Bag, String> {
+    let mut bag = Bag::new();
+    for entry in text.split(',') {
+        let (color, count) = entry
+            .split_once('=')
+            .ok_or(format!("Expected color=count, got '{entry}'"))?;
+        let count = count
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid count in '{entry}': {e}"))?;
+        bag.insert(color.trim().to_string(), count);
+    }
+    Ok(bag)
+}
+
+fn parse_number(pair: Pair<'_, Rule>) -> u32 {
+    pair.as_str().parse().expect("unable to parse number")
 }
 
-fn parse_number(slice: &[u8]) -> u32 {
-    //println!("Trying to parse {}", char::from(slice[0]));
-    let mut number: u32 = 0;
-    for (_i, &item) in slice.iter().enumerate() {
-        number = number * 10 + u32::from(item - b'0');
+fn analyze_draw(draw: Pair<'_, Rule>) -> Draw {
+    let mut parsed = Draw::new();
+    for cubes in draw.into_inner() {
+        let mut inner = cubes.into_inner();
+        let num = parse_number(inner.next().expect("cubes without number"));
+        let color = inner.next().expect("cubes without color").as_str();
+        parsed.push((color.to_string(), num));
     }
-    return number;
+    parsed
 }
 
-fn parse_game(games: &mut Vec<Game>, slice: &[u8]) {
+fn analyze_game(line: Pair<'_, Rule>) -> Game {
     let mut game = Game {
         game_id: 0,
-        max_red: 0,
-        max_green: 0,
-        max_blue: 0,
+        draws: Vec::new(),
     };
-    let mut start_num: usize = 0;
-    let mut num: u32 = 0;
-    let mut start_color: usize = 0;
-    let mut skip = false;
-
-    for i in 5..slice.len() {
-        if skip == true {
-            skip = false;
-            continue;
-        }
-        if slice[i] == b':' {
-            game.game_id = parse_number(&slice[5..i]);
-            skip = true; // skip space
-            start_num = i + 2;
-        } else if slice[i] == b' ' {
-            num = parse_number(&slice[start_num..i]);
-            start_color = i + 1;
-        } else if slice[i] == b',' || slice[i] == b';' {
-            match parse_color(&slice[start_color..i]) {
-                Color::Red => {
-                    if num > game.max_red {
-                        game.max_red = num;
-                    }
-                }
-                Color::Green => {
-                    if num > game.max_green {
-                        game.max_green = num;
-                    }
-                }
-                Color::Blue => {
-                    if num > game.max_blue {
-                        game.max_blue = num;
-                    }
-                }
-            }
-            start_num = i + 2;
-            skip = true; // skip space
-        }
-    }
-    match parse_color(&slice[start_color..slice.len()]) {
-        Color::Red => {
-            if num > game.max_red {
-                game.max_red = num;
+    for entry in line.into_inner() {
+        match entry.as_rule() {
+            Rule::game_identifier => {
+                let id = entry.into_inner().next().expect("game without id");
+                game.game_id = parse_number(id);
             }
-        }
-        Color::Green => {
-            if num > game.max_green {
-                game.max_green = num;
+            Rule::draw => game.draws.push(analyze_draw(entry)),
+            _ => {
+                println!("UNEXPECTED PARSE(Game) {}", entry.as_str());
             }
         }
-        Color::Blue => {
-            if num > game.max_blue {
-                game.max_blue = num;
+    }
+    game
+}
+
+fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Game> {
+    let mut games: Vec<Game> = Vec::new();
+
+    let unwrapped = parsed.next().unwrap();
+    for line in unwrapped.into_inner() {
+        match line.as_rule() {
+            Rule::game => games.push(analyze_game(line)),
+            Rule::EOI => {}
+            _ => {
+                println!("UNEXPECTED PARSE(File) {}", line.as_str());
             }
         }
     }
-    games.push(game);
+    games
 }
 
 fn main() {
-    let mut games: Vec<Game> = Vec::new();
-
     // setup input file
     let args: Vec<String> = env::args().collect();
     let path = &args[1];
 
+    let bag_text = match args.iter().position(|arg| arg == "--bag") {
+        Some(i) => args.get(i + 1).map_or("", String::as_str),
+        None => DEFAULT_BAG,
+    };
+    let bag = match parse_bag(bag_text) {
+        Ok(bag) => bag,
+        Err(e) => {
+            println!("ERR:  Could not parse bag: {e}");
+            return;
+        }
+    };
+
     let contents = fs::read_to_string(path).expect("Should have been able to read the file");
 
-    let bytes = contents.as_bytes();
-    let mut start = 0;
-    for (i, &item) in bytes.iter().enumerate() {
-        if item == b'\n' {
-            match bytes.get(start..i - 1) {
-                Some(slice) => parse_game(&mut games, slice),
-                None => println!("cannot extract {start}..{}", i - 1),
-            }
-            start = i + 1;
+    let games = match MyParser::parse(Rule::file, &contents) {
+        Ok(mut result) => analyze_file(&mut result),
+        Err(result) => {
+            println!("ERR:  Could not parse file: {result}");
+            return;
         }
-    }
+    };
     println!("Found {} Games", games.len());
 
     let mut sum: u32 = 0;
     let mut powers: u32 = 0;
     for game in games {
-        if game.max_red <= 12 && game.max_green <= 13 && game.max_blue <= 14 {
-            sum += game.game_id;
+        match game.first_violation(&bag) {
+            None => sum += game.game_id,
+            Some(violation) => {
+                let known = if bag.contains_key(&violation.color) {
+                    ""
+                } else {
+                    " (unknown color)"
+                };
+                println!(
+                    "ID{}: impossible, draw {} has {} {}{known}",
+                    game.game_id,
+                    violation.draw + 1,
+                    violation.num,
+                    violation.color
+                );
+            }
         }
 
-        let power: u32 = game.max_red * game.max_green * game.max_blue;
+        // bag colors the game never draws need 0 cubes, colors outside the bag still count
+        let minimal = game.minimal_bag();
+        let power: u32 = bag
+            .keys()
+            .chain(minimal.keys().filter(|color| !bag.contains_key(*color)))
+            .map(|color| minimal.get(color).map_or(0, |(num, _)| *num))
+            .product();
         powers += power;
 
-        println!(
-            "ID{}: R{}, G{},B{}, Pow{}",
-            game.game_id, game.max_red, game.max_green, game.max_blue, power
-        );
+        let needed: Vec<String> = minimal
+            .iter()
+            .map(|(color, (num, draw))| format!("{color} {num} (draw {})", draw + 1))
+            .collect();
+        println!("ID{}: {}, Pow{}", game.game_id, needed.join(", "), power);
     }
     println!("Sum is {sum}");
     println!("Power is {powers}");
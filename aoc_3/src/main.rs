@@ -1,279 +1,299 @@
 use std::env;
 use std::fs;
 
+use colored::Colorize;
+
 struct Entry {
-    active: bool,
+    line: usize,
     start_index: usize,
     end_index: usize,
     number: u32,
 }
 struct Symbol {
+    line: usize,
     index: usize,
-    adjacient_gears: u32,
-    gear_ratio: u32,
-    is_gear: bool,
+    symbol: char,
 }
 
-fn parse_number(bytes: &[u8], start: usize, end: usize) -> u32 {
-    //println!("Trying to parse {}", char::from(slice[0]));
-    let mut number: u32 = 0;
-    for i in start..end + 1 {
-        number = number * 10 + u32::from(bytes[i] - b'0');
-    }
-    return number;
+enum Cell {
+    Empty,
+    Entry(usize),
+    Symbol(usize),
 }
 
-fn add_entry(
-    entries: &mut Vec<Vec<Entry>>,
-    bytes: &[u8],
-    line: usize,
-    start: usize,
-    end: usize,
-    active: bool,
-) {
-    entries[line].push(Entry {
-        active: active,
-        start_index: start,
-        end_index: end,
-        number: parse_number(bytes, start, end),
-    });
+// every cell knows which entry or symbol occupies it, so adjacency is a lookup
+struct Schematic {
+    cells: Vec<Vec<Cell>>,
+    entries: Vec<Entry>,
+    symbols: Vec<Symbol>,
 }
 
-fn add_symbol(symbols: &mut Vec<Vec<Symbol>>, line: usize, index: usize, is_gear: bool) {
-    symbols[line].push(Symbol {
-        index: index,
-        adjacient_gears: 0,
-        gear_ratio: 1,
-        is_gear: is_gear,
-    });
+enum Aggregate {
+    Product,
+    Sum,
 }
 
-fn parse_line(
-    entries: &mut Vec<Vec<Entry>>,
-    symbols: &mut Vec<Vec<Symbol>>,
-    line: usize,
-    slice: &[u8],
-) {
-    let mut start_of_number = 0;
-    let mut parsing_number = false;
-    let mut is_already_near_symbol = false;
-    for (i, &item) in slice.iter().enumerate() {
-        if item == b'.' {
-            if parsing_number {
-                parsing_number = false;
-                add_entry(
-                    entries,
-                    slice,
-                    line,
-                    start_of_number,
-                    i - 1,
-                    is_already_near_symbol,
-                );
+struct GearRule {
+    symbols: Vec<char>,
+    neighbors: usize,
+    aggregate: Aggregate,
+}
+
+impl Schematic {
+    fn parse(contents: &str) -> Schematic {
+        let mut schematic = Schematic {
+            cells: Vec::new(),
+            entries: Vec::new(),
+            symbols: Vec::new(),
+        };
+        for (line, text) in contents.lines().enumerate() {
+            let mut cells: Vec<Cell> = Vec::new();
+            for (i, item) in text.chars().enumerate() {
+                if let Some(digit) = item.to_digit(10) {
+                    match cells.last() {
+                        Some(&Cell::Entry(id)) => {
+                            let entry = &mut schematic.entries[id];
+                            entry.number = entry.number * 10 + digit;
+                            entry.end_index = i;
+                        }
+                        _ => schematic.entries.push(Entry {
+                            line,
+                            start_index: i,
+                            end_index: i,
+                            number: digit,
+                        }),
+                    }
+                    cells.push(Cell::Entry(schematic.entries.len() - 1));
+                } else if item == '.' {
+                    cells.push(Cell::Empty);
+                } else {
+                    schematic.symbols.push(Symbol {
+                        line,
+                        index: i,
+                        symbol: item,
+                    });
+                    cells.push(Cell::Symbol(schematic.symbols.len() - 1));
+                }
             }
-            is_already_near_symbol = false;
-        } else if item >= b'0' && item <= b'9' {
-            // number
-            if parsing_number {
-                //
-            } else {
-                parsing_number = true;
-                start_of_number = i;
+            schematic.cells.push(cells);
+        }
+        schematic
+    }
+
+    fn cell(&self, line: usize, index: usize) -> Option<&Cell> {
+        self.cells.get(line).and_then(|cells| cells.get(index))
+    }
+
+    // all cells touching the rectangle (line, start..=end), including diagonals
+    fn surrounding(&self, line: usize, start: usize, end: usize) -> Vec<&Cell> {
+        let mut found = Vec::new();
+        for l in line.saturating_sub(1)..=line + 1 {
+            for i in start.saturating_sub(1)..=end + 1 {
+                if l == line && (start..=end).contains(&i) {
+                    continue;
+                }
+                if let Some(cell) = self.cell(l, i) {
+                    found.push(cell);
+                }
             }
-        } else if item == b'\n' {
-            panic!("found NL in Line");
-        } else {
-            add_symbol(symbols, line, i, item == b'*');
-            if parsing_number {
-                parsing_number = false;
-                add_entry(entries, slice, line, start_of_number, i - 1, true);
+        }
+        found
+    }
+
+    fn entries_adjacent_to_symbol(&self, symbol: usize) -> Vec<usize> {
+        let s = &self.symbols[symbol];
+        let mut found: Vec<usize> = Vec::new();
+        for cell in self.surrounding(s.line, s.index, s.index) {
+            if let Cell::Entry(id) = *cell {
+                if !found.contains(&id) {
+                    found.push(id);
+                }
             }
-            is_already_near_symbol = true;
         }
+        found
     }
-    if parsing_number {
-        add_entry(
-            entries,
-            slice,
-            line,
-            start_of_number,
-            slice.len() - 1,
-            is_already_near_symbol,
-        );
+
+    fn symbols_adjacent_to_entry(&self, entry: usize) -> Vec<usize> {
+        let e = &self.entries[entry];
+        let mut found: Vec<usize> = Vec::new();
+        for cell in self.surrounding(e.line, e.start_index, e.end_index) {
+            if let Cell::Symbol(id) = *cell {
+                found.push(id);
+            }
+        }
+        found
     }
-}
 
-fn in_range(symbol_index: usize, entry_start: usize, entry_end: usize) -> bool {
-    if entry_start == 0 {
-        return symbol_index <= entry_end + 1;
-    } else {
-        return symbol_index >= entry_start - 1 && symbol_index <= entry_end + 1;
+    fn is_part_number(&self, entry: usize) -> bool {
+        !self.symbols_adjacent_to_entry(entry).is_empty()
     }
 }
 
-fn solve_neighbors_across_lines(
-    entries: &mut Vec<Vec<Entry>>,
-    symbols: &Vec<Vec<Symbol>>,
-    lines: usize,
-) {
-    for i in 0..lines {
-        for entry in entries[i].iter_mut() {
-            // skip if already marked active
-            if entry.active {
-                continue;
-            }
-            if i != 0 {
-                for symbol in &symbols[i - 1] {
-                    if in_range(symbol.index, entry.start_index, entry.end_index) {
-                        entry.active = true;
-                    }
+impl GearRule {
+    fn parse(args: &[String]) -> Result<GearRule, String> {
+        let mut rule = GearRule {
+            symbols: vec!['*'],
+            neighbors: 2,
+            aggregate: Aggregate::Product,
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = |name: &str| iter.next().ok_or(format!("Missing value for {name}"));
+            match arg.as_str() {
+                "--gear-symbols" => rule.symbols = value("--gear-symbols")?.chars().collect(),
+                "--gear-neighbors" => {
+                    rule.neighbors = value("--gear-neighbors")?
+                        .parse()
+                        .map_err(|e| format!("Invalid neighbor count: {e}"))?
                 }
-            }
-            if i != lines - 1 {
-                for symbol in &symbols[i + 1] {
-                    if in_range(symbol.index, entry.start_index, entry.end_index) {
-                        entry.active = true;
+                "--gear-aggregate" => {
+                    rule.aggregate = match value("--gear-aggregate")?.as_str() {
+                        "product" => Aggregate::Product,
+                        "sum" => Aggregate::Sum,
+                        other => return Err(format!("Unknown aggregate '{other}'")),
                     }
                 }
+                _ => return Err(format!("Unexpected argument '{arg}'")),
             }
         }
+        Ok(rule)
     }
-}
 
-fn print_parsed(entries: &mut Vec<Vec<Entry>>, symbols: &mut Vec<Vec<Symbol>>, lines: usize) {
-    for i in 0..lines {
-        println!("Line {i}:");
-        for entry in &entries[i] {
-            println!(
-                "  E {} {}-{} {}",
-                if entry.active { "X" } else { "O" },
-                entry.start_index,
-                entry.end_index,
-                entry.number,
-            );
+    // the gear value of a symbol, or None if the symbol is no gear under this rule
+    fn evaluate(&self, schematic: &Schematic, symbol: usize) -> Option<u64> {
+        if !self.symbols.contains(&schematic.symbols[symbol].symbol) {
+            return None;
         }
-        for symbol in &symbols[i] {
-            if symbol.is_gear {
-                println!(
-                    "  S {} ({}:{})",
-                    symbol.index, symbol.adjacient_gears, symbol.gear_ratio
-                );
-            } else {
-                println!("  S {}", symbol.index);
-            }
+        let adjacent = schematic.entries_adjacent_to_symbol(symbol);
+        if adjacent.len() != self.neighbors {
+            return None;
         }
-        println!("");
+        let values = adjacent
+            .iter()
+            .map(|&id| u64::from(schematic.entries[id].number));
+        Some(match self.aggregate {
+            Aggregate::Product => values.product(),
+            Aggregate::Sum => values.sum(),
+        })
     }
 }
 
-fn count_part_numbers(entries: &Vec<Vec<Entry>>, lines: usize) -> u32 {
-    let mut sum: u32 = 0;
-    for i in 0..lines {
-        for entry in &entries[i] {
-            if entry.active {
-                sum += entry.number;
-            }
+fn print_parsed(schematic: &Schematic, rule: &GearRule) {
+    for (id, entry) in schematic.entries.iter().enumerate() {
+        println!(
+            "  E {} {}:{}-{} {}",
+            if schematic.is_part_number(id) {
+                "X"
+            } else {
+                "O"
+            },
+            entry.line,
+            entry.start_index,
+            entry.end_index,
+            entry.number,
+        );
+    }
+    for (id, symbol) in schematic.symbols.iter().enumerate() {
+        let adjacent = schematic.entries_adjacent_to_symbol(id);
+        match rule.evaluate(schematic, id) {
+            Some(value) => println!(
+                "  S {} {}:{} ({}:{value})",
+                symbol.symbol,
+                symbol.line,
+                symbol.index,
+                adjacent.len()
+            ),
+            None => println!(
+                "  S {} {}:{} ({})",
+                symbol.symbol,
+                symbol.line,
+                symbol.index,
+                adjacent.len()
+            ),
         }
     }
-    return sum;
 }
 
-fn calc_gears(entries: &Vec<Vec<Entry>>, symbols: &mut Vec<Vec<Symbol>>, lines: usize) {
-    for i in 0..lines {
-        for symbol in symbols[i].iter_mut() {
-            if symbol.adjacient_gears > 2 || !symbol.is_gear {
-                continue;
-            }
-            if i != 0 {
-                for entry in &entries[i - 1] {
-                    if in_range(symbol.index, entry.start_index, entry.end_index) {
-                        symbol.adjacient_gears += 1;
-                        if symbol.adjacient_gears <= 2 {
-                            symbol.gear_ratio *= entry.number;
-                        }
+// part numbers green, other numbers red, gears yellow, symbols without numbers magenta
+fn render(schematic: &Schematic, rule: &GearRule) {
+    for (line, cells) in schematic.cells.iter().enumerate() {
+        let mut gears: Vec<String> = Vec::new();
+        let mut i = 0;
+        while i < cells.len() {
+            match cells[i] {
+                Cell::Empty => print!("{}", ".".dimmed()),
+                Cell::Entry(id) => {
+                    let entry = &schematic.entries[id];
+                    let width = entry.end_index - entry.start_index + 1;
+                    let text = format!("{:0width$}", entry.number);
+                    if schematic.is_part_number(id) {
+                        print!("{}", text.green());
+                    } else {
+                        print!("{}", text.red());
                     }
+                    i = entry.end_index;
                 }
-            }
-            if symbol.adjacient_gears > 2 {
-                continue;
-            }
-            for entry in &entries[i] {
-                if in_range(symbol.index, entry.start_index, entry.end_index) {
-                    symbol.adjacient_gears += 1;
-                    if symbol.adjacient_gears <= 2 {
-                        symbol.gear_ratio *= entry.number;
-                    }
-                }
-            }
-            if symbol.adjacient_gears > 2 {
-                continue;
-            }
-            if i != lines - 1 {
-                for entry in &entries[i + 1] {
-                    if in_range(symbol.index, entry.start_index, entry.end_index) {
-                        symbol.adjacient_gears += 1;
-                        if symbol.adjacient_gears <= 2 {
-                            symbol.gear_ratio *= entry.number;
-                        }
+                Cell::Symbol(id) => {
+                    let symbol = schematic.symbols[id].symbol.to_string();
+                    if let Some(value) = rule.evaluate(schematic, id) {
+                        print!("{}", symbol.yellow().bold());
+                        gears.push(format!("{i}={value}"));
+                    } else if schematic.entries_adjacent_to_symbol(id).is_empty() {
+                        print!("{}", symbol.on_magenta());
+                    } else {
+                        print!("{}", symbol.blue());
                     }
                 }
             }
+            i += 1;
         }
-    }
-}
-
-fn sum_gears(symbols: &Vec<Vec<Symbol>>, lines: usize) -> u32 {
-    let mut sum: u32 = 0;
-    for i in 0..lines {
-        for symbol in &symbols[i] {
-            if symbol.adjacient_gears == 2 {
-                sum += symbol.gear_ratio;
-            }
+        if gears.is_empty() {
+            println!();
+        } else {
+            println!("  {:>3}: {}", line, gears.join(" ").yellow());
         }
     }
-    return sum;
 }
 
 fn main() {
-    let mut entries: Vec<Vec<Entry>> = Vec::new();
-    let mut symbols: Vec<Vec<Symbol>> = Vec::new();
-
     let args: Vec<String> = env::args().collect();
     let path = &args[1];
 
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
+    let render_mode = args.iter().any(|arg| arg == "--render");
+    let flags: Vec<String> = args[2..]
+        .iter()
+        .filter(|arg| *arg != "--render")
+        .cloned()
+        .collect();
 
-    let bytes = contents.as_bytes();
-    let mut start = 0;
-    let mut cur_line = 0;
+    let rule = match GearRule::parse(&flags) {
+        Ok(rule) => rule,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
 
-    for (i, &item) in bytes.iter().enumerate() {
-        if item == b'\n' {
-            // make space in vectors
-            entries.push(Vec::new());
-            symbols.push(Vec::new());
+    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
 
-            // parse line
-            match bytes.get(start..i - 1) {
-                Some(slice) => parse_line(&mut entries, &mut symbols, cur_line, slice),
-                None => println!("cannot extract on line {cur_line}: {start}..{}", i),
-            }
+    let schematic = Schematic::parse(&contents);
 
-            // keep track of running variables
-            cur_line += 1;
-            start = i + 1;
-        }
+    if render_mode {
+        render(&schematic, &rule);
+    } else {
+        print_parsed(&schematic, &rule);
     }
 
-    let total_lines = cur_line;
-
-    solve_neighbors_across_lines(&mut entries, &mut symbols, total_lines);
-    calc_gears(&entries, &mut symbols, total_lines);
-
-    print_parsed(&mut entries, &mut symbols, total_lines);
+    let part_numbers: u32 = (0..schematic.entries.len())
+        .filter(|&id| schematic.is_part_number(id))
+        .map(|id| schematic.entries[id].number)
+        .sum();
+    let gears: u64 = (0..schematic.symbols.len())
+        .filter_map(|id| rule.evaluate(&schematic, id))
+        .sum();
 
     println!(
-        "{total_lines} lines, sum of p.numbers: {}, sum of gear ratios: {}",
-        count_part_numbers(&entries, total_lines),
-        sum_gears(&symbols, total_lines)
+        "{} lines, sum of p.numbers: {part_numbers}, sum of gear ratios: {gears}",
+        schematic.cells.len()
     );
 }
@@ -1,8 +1,11 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::env;
-use std::fs;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
 
 use pest::iterators::Pair;
-use pest::iterators::Pairs;
 use pest::Parser;
 use pest_derive::Parser;
 
@@ -11,143 +14,158 @@ use pest_derive::Parser;
 struct MyParser;
 
 struct Scratchcard {
-    counts: u32,
-    score: usize,
+    id: u32,
+    matches: usize,
 }
 
-fn analyze_list(list: Pair<'_, Rule>) -> Vec<u32> {
-    let mut parsed: Vec<u32> = Vec::new();
-
-    for num in list.into_inner() {
-        match num.as_rule() {
-            Rule::number => {
-                let i: u32 = num.as_str().parse().expect("unable to parse number");
-                parsed.push(i);
-            }
-            Rule::EOI => {
-                println!("   EOI {}", num.as_str());
-            }
-            _ => {
-                println!("UNEXPECTED PARSE(List) {}", num.as_str());
-            }
+impl Scratchcard {
+    // the first match is worth one point, every further match doubles it,
+    // None once that no longer fits into u64
+    fn points(&self) -> Option<u64> {
+        match self.matches {
+            0 => Some(0),
+            matches => u32::try_from(matches - 1)
+                .ok()
+                .and_then(|shift| 1u64.checked_shl(shift)),
         }
     }
-
-    parsed.sort();
-    return parsed;
 }
 
-fn analyze_line(line: Pair<'_, Rule>) -> usize {
-    let mut win_list: Option<Vec<u32>> = None;
-    let mut num_list: Option<Vec<u32>> = None;
-    for entry in line.into_inner() {
-        match entry.as_rule() {
-            Rule::winning_list => {
-                println!("   Wins {}", entry.as_str());
-                win_list = Some(analyze_list(entry));
-            }
-            Rule::number_list => {
-                println!("   Nums {}", entry.as_str());
-                num_list = Some(analyze_list(entry));
-            }
-            Rule::EOI => {
-                println!(" EOI {}", entry.as_str());
-            }
-            Rule::game_identifier => {
-                println!(" Card {}", entry.as_str());
-            }
-            _ => {
-                println!("UNEXPECTED PARSE(Line) {}", entry.as_str());
-            }
-        }
-    }
+// copies won by earlier cards, the front belongs to the next card to be read
+struct Cascade {
+    pending: VecDeque<u64>,
+    total: u64,
+}
 
-    match (win_list, num_list) {
-        (Some(wins), Some(nums)) => {
-            let mut points: usize = 0;
-            for num in nums {
-                if wins.contains(&num) {
-                    points += 1;
-                }
-            }
-            return points;
-        }
-        (None, Some(_)) => {
-            println!("wins were not found");
-        }
-        (Some(_), None) => {
-            println!("nums were not found");
+impl Cascade {
+    // returns how many instances of the card are held once it is reached,
+    // None if a count no longer fits into u64
+    fn add(&mut self, card: &Scratchcard) -> Option<u64> {
+        let counts = self.pending.pop_front().unwrap_or(0).checked_add(1)?;
+        if self.pending.len() < card.matches {
+            self.pending.resize(card.matches, 0);
         }
-        (None, None) => {
-            println!("wins and nums were not found");
+        for pending in self.pending.iter_mut().take(card.matches) {
+            *pending = pending.checked_add(counts)?;
         }
+        self.total = self.total.checked_add(counts)?;
+        Some(counts)
     }
-    return 0;
 }
 
-fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Scratchcard> {
-    let mut s_cards: Vec<Scratchcard> = Vec::new();
+fn analyze_list(list: Pair<'_, Rule>) -> impl Iterator<Item = u32> + '_ {
+    list.into_inner()
+        .map(|num| num.as_str().parse().expect("unable to parse number"))
+}
 
-    let unwrapped = parsed.next().unwrap();
-    for line in unwrapped.into_inner() {
-        match line.as_rule() {
-            Rule::line => {
-                let score = analyze_line(line);
-                s_cards.push(Scratchcard {
-                    counts: 1,
-                    score: score,
-                });
+fn analyze_card(card: Pair<'_, Rule>) -> Scratchcard {
+    let mut id: u32 = 0;
+    let mut wins: HashSet<u32> = HashSet::new();
+    let mut matches: usize = 0;
+    for entry in card.into_inner() {
+        match entry.as_rule() {
+            Rule::game_identifier => {
+                let number = entry.into_inner().next().expect("card without number");
+                id = number.as_str().parse().expect("unable to parse number");
             }
-            Rule::EOI => {
-                println!("EOI {}", line.as_str());
+            Rule::winning_list => wins.extend(analyze_list(entry)),
+            Rule::number_list => {
+                matches = analyze_list(entry).filter(|num| wins.contains(num)).count();
             }
             _ => {
-                println!("UNEXPECTED PARSE(File) {}", line.as_str());
+                println!("UNEXPECTED PARSE(Card) {}", entry.as_str());
             }
         }
     }
-    return s_cards;
+    Scratchcard { id, matches }
 }
 
-fn collect_prizes(cards: &mut Vec<Scratchcard>) -> u32 {
-    for i in 0..cards.len() {
-        if cards[i].score > 0 {
-            for j in i + 1..i + 1 + cards[i].score {
-                if j >= cards.len() {
-                    break;
-                }
-                cards[j].counts += cards[i].counts;
-            }
-        }
-    }
-
-    let score = cards.iter().fold(0, |sum, card| sum + card.counts);
+fn analyze_line(text: &str) -> Result<Scratchcard, String> {
+    let mut parsed = MyParser::parse(Rule::line, text).map_err(|e| e.to_string())?;
+    let line = parsed.next().expect("line without content");
+    let card = line
+        .into_inner()
+        .find(|entry| entry.as_rule() == Rule::card)
+        .expect("line without card");
+    Ok(analyze_card(card))
+}
 
-    for card in cards {
-        println!("{}", card.counts);
-    }
-    return score;
+// awarded cards are clipped at last_id, copies beyond the last card are never held
+fn print_trace(card: &Scratchcard, points: u64, counts: u64, last_id: u32) {
+    let end = (card.id as usize + card.matches).min(last_id as usize);
+    let awarded = match end.saturating_sub(card.id as usize) {
+        0 => "no cards".to_string(),
+        1 => format!("card {}", card.id + 1),
+        _ => format!("cards {}-{end}", card.id + 1),
+    };
+    println!(
+        "Card {}: {} matches, {points} points, copies of {awarded}, {counts} copies held",
+        card.id, card.matches,
+    );
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let path = &args[1];
-
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
-
-    let parse_result = MyParser::parse(Rule::file, &contents);
-
-    match parse_result {
-        Ok(mut result) => {
-            println!("Parse file OK");
-            println!("==========");
-            let mut cards = analyze_file(&mut result);
-            println!("==========");
-            let card_count = collect_prizes(&mut cards);
-            println!("Card count is {card_count}")
+    let trace = args.iter().any(|arg| arg == "--trace");
+
+    let file = File::open(path).expect("Should have been able to open the file");
+
+    let mut points: u64 = 0;
+    let mut traced: VecDeque<(Scratchcard, u64, u64)> = VecDeque::new();
+    let mut cascade = Cascade {
+        pending: VecDeque::new(),
+        total: 0,
+    };
+
+    // cards are read one at a time, only the copies still owed to upcoming cards are kept
+    for (i, text) in BufReader::new(file).lines().enumerate() {
+        let text = text.expect("Should have been able to read the line");
+        if text.trim().is_empty() {
+            continue;
         }
-        Err(result) => {
-            println!("ERR:  Could not parse file: {result}");
+        let card = match analyze_line(&text) {
+            Ok(card) => card,
+            Err(e) => {
+                println!("ERR:  Could not parse line {}: {e}", i + 1);
+                return;
+            }
+        };
+        let Some(card_points) = card.points() else {
+            println!("ERR:  Points of card {} overflowed u64", card.id);
+            return;
+        };
+        let Some(sum) = points.checked_add(card_points) else {
+            println!("ERR:  Points overflowed u64 at card {}", card.id);
+            return;
+        };
+        points = sum;
+        let Some(counts) = cascade.add(&card) else {
+            println!("ERR:  Card count overflowed u64 at card {}", card.id);
+            return;
+        };
+        if trace {
+            // a card's line waits until the cards it awards are known to exist
+            let last_id = card.id;
+            traced.push_back((card, card_points, counts));
+            while let Some((front, points, counts)) = traced.front() {
+                if front.id as usize + front.matches > last_id as usize {
+                    break;
+                }
+                print_trace(front, *points, *counts, last_id);
+                traced.pop_front();
+            }
         }
     }
+    if let Some((last, _, _)) = traced.back() {
+        let last_id = last.id;
+        for (card, points, counts) in &traced {
+            print_trace(card, *points, *counts, last_id);
+        }
+    }
+    if cascade.pending.iter().any(|&pending| pending > 0) {
+        println!("Copies won beyond the last card were dropped");
+    }
+    println!("Points are {points}");
+    println!("Card count is {}", cascade.total)
 }
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::env;
 use std::fs;
 
@@ -10,6 +12,8 @@ use pest_derive::Parser;
 #[grammar = "grammar.pest"]
 struct MyParser;
 
+type Seeds = Vec<(usize, usize)>;
+
 struct Mapping {
     start_source: usize,
     start_dest: usize,
@@ -30,46 +34,14 @@ impl Map {
     }
 }
 
-fn analyze_number_pair(pair: Pair<'_, Rule>) -> Result<(usize, usize), &str> {
-    let mut num: Option<usize> = None;
-    let mut count: Option<usize> = None;
-    for n in pair.into_inner() {
-        match n.as_rule() {
-            Rule::number => match n.as_str().parse::<usize>() {
-                Ok(i) => num = Some(i),
-                Err(e) => println!("Error parsing number {e}"),
-            },
-            Rule::count => match n.as_str().parse::<usize>() {
-                Ok(i) => count = Some(i),
-                Err(e) => println!("Error parsing number {e}"),
-            },
-            Rule::EOI => {
-                println!("      EOI {}", n.as_str());
-            }
-            _ => {
-                println!("UNEXPECTED PARSE(NumberPair) {}", n.as_str());
-            }
-        }
-    }
-
-    match (num, count) {
-        (Some(i), Some(j)) => return Ok((i, j)),
-        _ => return Err("Could not parse pair"),
-    }
-}
-
-fn analyze_number_list(entry: Pair<'_, Rule>) -> Vec<(usize, usize)> {
-    let mut seeds: Vec<(usize, usize)> = Vec::new();
+fn analyze_number_list(entry: Pair<'_, Rule>) -> Vec<usize> {
+    let mut numbers: Vec<usize> = Vec::new();
 
     for number in entry.into_inner() {
         match number.as_rule() {
-            Rule::number_pair => match analyze_number_pair(number) {
-                Ok((num, count)) => {
-                    if count > 0 {
-                        seeds.push((num, count));
-                    }
-                }
-                Err(_e) => {}
+            Rule::number => match number.as_str().parse::<usize>() {
+                Ok(i) => numbers.push(i),
+                Err(e) => println!("Error parsing number {e}"),
             },
             Rule::EOI => {
                 println!("    EOI {}", number.as_str());
@@ -80,11 +52,11 @@ fn analyze_number_list(entry: Pair<'_, Rule>) -> Vec<(usize, usize)> {
         }
     }
 
-    return seeds;
+    numbers
 }
 
-fn analyze_seeds(parsed: Pair<'_, Rule>) -> Option<Vec<(usize, usize)>> {
-    let mut seeds: Option<Vec<(usize, usize)>> = None;
+fn analyze_seeds(parsed: Pair<'_, Rule>) -> Option<Vec<usize>> {
+    let mut seeds: Option<Vec<usize>> = None;
     for entry in parsed.into_inner() {
         match entry.as_rule() {
             Rule::number_list => seeds = Some(analyze_number_list(entry)),
@@ -97,7 +69,32 @@ fn analyze_seeds(parsed: Pair<'_, Rule>) -> Option<Vec<(usize, usize)>> {
         }
     }
 
-    return seeds;
+    seeds
+}
+
+enum SeedMode {
+    Single,
+    Ranges,
+}
+
+// turns the seed numbers into (start, count) ranges
+fn interpret_seeds(numbers: &[usize], mode: &SeedMode) -> Seeds {
+    match mode {
+        SeedMode::Single => numbers.iter().map(|&num| (num, 1)).collect(),
+        SeedMode::Ranges => {
+            if numbers.len() % 2 == 1 {
+                println!(
+                    "odd number of seed values, {} is ignored as range start",
+                    numbers[numbers.len() - 1]
+                );
+            }
+            numbers
+                .chunks_exact(2)
+                .map(|pair| (pair[0], pair[1]))
+                .filter(|&(_, count)| count > 0)
+                .collect()
+        }
+    }
 }
 
 fn analyze_map_header(header: Pair<'_, Rule>) -> Result<(String, String), &'static str> {
@@ -120,8 +117,8 @@ fn analyze_map_header(header: Pair<'_, Rule>) -> Result<(String, String), &'stat
         }
     }
     match (from, to) {
-        (Some(f), Some(t)) => return Ok((f, t)),
-        _ => return Err("Entry could not be parsed completely"),
+        (Some(f), Some(t)) => Ok((f, t)),
+        _ => Err("Entry could not be parsed completely"),
     }
 }
 fn analyze_map_entry(entry: Pair<'_, Rule>) -> Result<Mapping, &'static str> {
@@ -157,14 +154,12 @@ fn analyze_map_entry(entry: Pair<'_, Rule>) -> Result<Mapping, &'static str> {
         }
     }
     match (start_source, start_dest, len) {
-        (Some(start_source), Some(start_dest), Some(len)) => {
-            return Ok(Mapping {
-                start_source: start_source,
-                start_dest: start_dest,
-                len: len,
-            });
-        }
-        _ => return Err("Entry could not be parsed completely"),
+        (Some(start_source), Some(start_dest), Some(len)) => Ok(Mapping {
+            start_source,
+            start_dest,
+            len,
+        }),
+        _ => Err("Entry could not be parsed completely"),
     }
 }
 
@@ -193,14 +188,12 @@ fn analyze_map(entry: Pair<'_, Rule>) -> Result<Map, &str> {
             }
         }
     }
-    return Ok(map);
+    Ok(map)
 }
 
-fn analyze_file(
-    parsed: &mut Pairs<'_, Rule>,
-) -> Result<(Vec<(usize, usize)>, Vec<Map>), &'static str> {
+fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Result<(Vec<usize>, Vec<Map>), &'static str> {
     let unwrapped = parsed.next().unwrap();
-    let mut seeds: Option<Vec<(usize, usize)>> = None;
+    let mut seeds: Option<Vec<usize>> = None;
     let mut maps: Vec<Map> = Vec::new();
 
     for line in unwrapped.into_inner() {
@@ -221,145 +214,468 @@ fn analyze_file(
         }
     }
     match seeds {
-        Some(s) => return Ok((s, maps)),
+        Some(s) => Ok((s, maps)),
         None => Err("No seeds parsed"),
     }
 }
 
-fn get_next_map<'a>(maps: &'a Vec<Map>, precedent: Option<&Map>) -> Option<&'a Map> {
-    match precedent {
-        Some(precedent) => return maps.iter().find(|m| m.from == precedent.to),
-        None => return maps.iter().find(|m| m.from == "seed".to_string()),
+fn get_next_map<'a>(maps: &'a [Map], from: &str) -> Option<&'a Map> {
+    maps.iter().find(|m| m.from == from)
+}
+
+fn find_cycles<'a>(
+    outgoing: &BTreeMap<&'a str, Vec<&'a str>>,
+    category: &'a str,
+    path: &mut Vec<&'a str>,
+    done: &mut BTreeSet<&'a str>,
+    problems: &mut Vec<String>,
+) {
+    if let Some(pos) = path.iter().position(|&c| c == category) {
+        problems.push(format!("cycle: {} -> {category}", path[pos..].join(" -> ")));
+        return;
     }
+    if !done.insert(category) {
+        return;
+    }
+    path.push(category);
+    for next in outgoing.get(category).into_iter().flatten() {
+        find_cycles(outgoing, next, path, done, problems);
+    }
+    path.pop();
 }
 
-fn print_mapping(mapped_to: &Vec<(usize, usize)>, precedent: Option<&Map>) {
-    print!(
-        "{}: ",
-        precedent.map_or("seed".to_string(), |p| p.from.clone())
-    );
+// reports cycles, branching and dangling categories and overlapping source ranges
+fn validate(maps: &[Map]) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    let mut outgoing: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for m in maps {
+        outgoing.entry(&m.from).or_default().push(&m.to);
+    }
 
-    for (num, cnt) in mapped_to {
-        print!("{},{} ", num, cnt);
+    for (from, to) in &outgoing {
+        if to.len() > 1 {
+            problems.push(format!("{from} branches into {}", to.join(", ")));
+        }
     }
-    println!("");
+
+    let mut done: BTreeSet<&str> = BTreeSet::new();
+    if outgoing.contains_key("seed") {
+        find_cycles(&outgoing, "seed", &mut Vec::new(), &mut done, &mut problems);
+    } else {
+        problems.push("no map starts at seed".to_string());
+    }
+    for m in maps {
+        if !done.contains(m.from.as_str()) {
+            problems.push(format!("{}-to-{} is not reachable from seed", m.from, m.to));
+        }
+    }
+    let reached = done.clone();
+    for &from in outgoing.keys() {
+        find_cycles(&outgoing, from, &mut Vec::new(), &mut done, &mut problems);
+    }
+
+    let ends: BTreeSet<&str> = maps
+        .iter()
+        .filter(|m| reached.contains(m.from.as_str()) && !outgoing.contains_key(m.to.as_str()))
+        .map(|m| m.to.as_str())
+        .collect();
+    if ends.len() > 1 {
+        problems.push(format!(
+            "dangling categories, the chain ends in {}",
+            ends.into_iter().collect::<Vec<&str>>().join(", ")
+        ));
+    }
+
+    for m in maps {
+        // empty mappings cover no values, from_map skips them as well
+        let mut mappings: Vec<&Mapping> = m.mappings.iter().filter(|m| m.len > 0).collect();
+        mappings.sort_by_key(|mapping| mapping.start_source);
+        // compare against the mapping reaching furthest so far, not just the previous one
+        let mut furthest: Option<&Mapping> = None;
+        for mapping in mappings {
+            if let Some(w) = furthest {
+                if w.start_source + w.len > mapping.start_source {
+                    problems.push(format!(
+                        "{}-to-{}: source {}..{} overlaps {}..{}",
+                        m.from,
+                        m.to,
+                        w.start_source,
+                        w.start_source + w.len,
+                        mapping.start_source,
+                        mapping.start_source + mapping.len
+                    ));
+                }
+            }
+            if furthest.is_none_or(|w| mapping.start_source + mapping.len > w.start_source + w.len)
+            {
+                furthest = Some(mapping);
+            }
+        }
+    }
+    problems
+}
+
+fn shift(value: usize, offset: i64) -> usize {
+    (value as i128 + offset as i128) as usize
+}
+
+// values in start..end are moved by offset
+#[derive(Clone, Copy)]
+struct Piece {
+    start: usize,
+    end: usize,
+    offset: i64,
 }
 
-fn intersects(start_a: usize, len_a: usize, start_b: usize, len_b: usize) -> bool {
-    let start_a_intersects = start_a >= start_b && start_a < start_b + len_b;
-    let start_b_intersects = start_b >= start_a && start_b < start_a + len_a;
-    return start_a_intersects || start_b_intersects;
+// a total function on 0..usize::MAX, pieces are sorted and cover the range without gaps
+struct Piecewise {
+    pieces: Vec<Piece>,
 }
 
-fn print_maps(maps: &Vec<Map>) {
-    for map in maps {
-        println!("Map: {}->{}", map.from, map.to);
-        for mapping in &map.mappings {
-            println!(
-                "{}-{} -> {}-{}, ({})",
-                mapping.start_source,
-                mapping.start_source + mapping.len,
-                mapping.start_dest,
-                mapping.start_dest + mapping.len,
-                mapping.len
-            );
+impl Piecewise {
+    fn identity() -> Self {
+        Self {
+            pieces: vec![Piece {
+                start: 0,
+                end: usize::MAX,
+                offset: 0,
+            }],
+        }
+    }
+
+    // unmapped values keep their number, on overlaps the mapping starting first wins
+    fn from_map(map: &Map) -> Self {
+        let mut mappings: Vec<&Mapping> = map.mappings.iter().filter(|m| m.len > 0).collect();
+        mappings.sort_by_key(|m| m.start_source);
+
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut pos = 0;
+        for m in mappings {
+            let end = m.start_source + m.len;
+            if end <= pos {
+                continue;
+            }
+            if m.start_source > pos {
+                pieces.push(Piece {
+                    start: pos,
+                    end: m.start_source,
+                    offset: 0,
+                });
+            }
+            pieces.push(Piece {
+                start: pos.max(m.start_source),
+                end,
+                offset: m.start_dest as i64 - m.start_source as i64,
+            });
+            pos = end;
+        }
+        pieces.push(Piece {
+            start: pos,
+            end: usize::MAX,
+            offset: 0,
+        });
+        Self { pieces }.merged()
+    }
+
+    fn merged(self) -> Self {
+        let mut pieces: Vec<Piece> = Vec::with_capacity(self.pieces.len());
+        for piece in self.pieces {
+            match pieces.last_mut() {
+                Some(last) if last.offset == piece.offset && last.end == piece.start => {
+                    last.end = piece.end;
+                }
+                _ => pieces.push(piece),
+            }
         }
+        Self { pieces }
+    }
+
+    // index of the piece containing value
+    fn find(&self, value: usize) -> usize {
+        self.pieces.partition_point(|p| p.end <= value)
     }
+
+    fn apply(&self, value: usize) -> usize {
+        shift(value, self.pieces[self.find(value)].offset)
+    }
+
+    // applies self first and next afterwards
+    fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut pieces: Vec<Piece> = Vec::new();
+        for piece in &self.pieces {
+            let low = shift(piece.start, piece.offset);
+            let high = shift(piece.end, piece.offset);
+            for n in &next.pieces[next.find(low)..] {
+                if n.start >= high {
+                    break;
+                }
+                pieces.push(Piece {
+                    start: shift(low.max(n.start), -piece.offset),
+                    end: shift(high.min(n.end), -piece.offset),
+                    offset: piece.offset + n.offset,
+                });
+            }
+        }
+        Piecewise { pieces }.merged()
+    }
+
+    // all values mapped into start..end, as sorted and merged ranges
+    fn preimage(&self, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for piece in &self.pieces {
+            let low = (start as i128 - piece.offset as i128).max(piece.start as i128);
+            let high = (end as i128 - piece.offset as i128).min(piece.end as i128);
+            if low < high {
+                ranges.push((low as usize, high as usize));
+            }
+        }
+        merge_ranges(ranges)
+    }
+
+    // lowest value the range start..end is mapped to
+    fn min_over(&self, start: usize, end: usize) -> Option<usize> {
+        self.pieces[self.find(start)..]
+            .iter()
+            .take_while(|p| p.start < end)
+            .map(|p| shift(p.start.max(start), p.offset))
+            .min()
+    }
+
+    fn print(&self, from: &str, to: &str) {
+        println!("{:>12} {:>12} {:>12}", from, "", to);
+        for piece in &self.pieces {
+            let end = if piece.end == usize::MAX {
+                "..".to_string()
+            } else {
+                (piece.end - 1).to_string()
+            };
+            println!("{:>12} {:>12} {:>+12}", piece.start, end, piece.offset);
+        }
+    }
+}
+
+fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.1 >= start => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+impl Map {
+    // all source ranges that this map sends into the given ranges
+    fn inverse(&self, ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let function = Piecewise::from_map(self);
+        merge_ranges(
+            ranges
+                .iter()
+                .flat_map(|&(start, end)| function.preimage(start, end))
+                .collect(),
+        )
+    }
+}
+
+// depth first search for maps leading from one category to another
+fn find_path<'a>(
+    maps: &'a [Map],
+    from: &str,
+    to: &str,
+    visited: &mut Vec<&'a str>,
+) -> Option<Vec<&'a Map>> {
+    if from == to {
+        return Some(Vec::new());
+    }
+    for m in maps.iter().filter(|m| m.from == from) {
+        if visited.contains(&m.to.as_str()) {
+            continue;
+        }
+        visited.push(&m.to);
+        if let Some(mut rest) = find_path(maps, &m.to, to, visited) {
+            rest.insert(0, m);
+            return Some(rest);
+        }
+    }
+    None
+}
+
+// the maps from one category to another, or up to the end of the chain without a target
+fn get_chain<'a>(maps: &'a [Map], from: &str, to: Option<&str>) -> Result<Vec<&'a Map>, String> {
+    if let Some(to) = to {
+        let mut visited: Vec<&str> = Vec::new();
+        return find_path(maps, from, to, &mut visited)
+            .ok_or(format!("no maps lead from {from} to {to}"));
+    }
+    let mut chain: Vec<&Map> = Vec::new();
+    let mut category = from;
+    while let Some(m) = get_next_map(maps, category) {
+        if m.to == from || chain.iter().any(|c| c.to == m.to) {
+            return Err(format!(
+                "maps starting at {from} run into a cycle at {}",
+                m.to
+            ));
+        }
+        chain.push(m);
+        category = &m.to;
+    }
+    Ok(chain)
+}
+
+// composes a chain of maps into a single function
+fn compose_chain(chain: &[&Map]) -> Piecewise {
+    chain.iter().fold(Piecewise::identity(), |composed, m| {
+        composed.then(&Piecewise::from_map(m))
+    })
+}
+
+// walks the chain backwards, returning the source ranges ending up in start..end
+fn inverse_chain(chain: &[&Map], start: usize, end: usize) -> Vec<(usize, usize)> {
+    chain
+        .iter()
+        .rev()
+        .fold(vec![(start, end)], |ranges, m| m.inverse(&ranges))
+}
+
+fn parse_window(text: &str) -> Option<(usize, usize)> {
+    match text.split_once('-') {
+        Some((low, high)) => Some((
+            low.trim().parse().ok()?,
+            high.trim().parse::<usize>().ok()? + 1,
+        )),
+        None => {
+            let value: usize = text.trim().parse().ok()?;
+            Some((value, value + 1))
+        }
+    }
+}
+
+fn solve_seeding(seeds: &[(usize, usize)], composed: &Piecewise) -> Option<usize> {
+    seeds
+        .iter()
+        .filter_map(|&(num, cnt)| composed.min_over(num, num + cnt))
+        .min()
+}
+
+struct Options {
+    path: String,
+    table: bool,
+    lookups: Vec<usize>,
+    inverse: Option<(usize, usize)>,
+    from: String,
+    to: Option<String>,
+    seed_mode: SeedMode,
 }
 
-fn solve_seeding(seeds: Vec<(usize, usize)>, maps: Vec<Map>) -> usize {
-    //print_maps(&maps);
-
-    let mut mapped_to = seeds;
-    let mut precedent: Option<&Map> = None;
-
-    loop {
-        //print_mapping(&mapped_to, precedent);
-        match get_next_map(&maps, precedent) {
-            Some(m) => {
-                precedent = Some(m);
-                mapped_to = mapped_to
-                    .iter()
-                    .flat_map(|&(num, cnt)| {
-                        let mut mapped = Vec::new();
-                        let mut remaining = vec![(num, cnt)];
-                        m.mappings.iter().for_each(|m| {
-                            remaining = remaining
-                                .iter()
-                                .flat_map(|&(start, len)| {
-                                    if m.start_source <= start
-                                        && m.start_source + m.len >= start + len
-                                    {
-                                        // is completely moved
-                                        mapped.push((start + m.start_dest - m.start_source, len));
-                                        return vec![];
-                                    } else if m.start_source <= start
-                                        && m.start_source + m.len > start
-                                    {
-                                        // only starting part is moved
-                                        let moved_len = m.start_source + m.len - start;
-                                        mapped.push((
-                                            start + m.start_dest - m.start_source,
-                                            moved_len,
-                                        ));
-                                        return vec![(start + moved_len, len - moved_len)];
-                                    } else if m.start_source < start + len
-                                        && m.start_source + m.len >= start + len
-                                    {
-                                        // only ending part is moved
-                                        let moved_len = start + len - m.start_source;
-                                        mapped.push((m.start_dest, moved_len));
-                                        return vec![(start, len - moved_len)];
-                                    } else if m.start_source > start
-                                        && m.start_source + m.len < start + len
-                                    {
-                                        // only part in the middle
-                                        let first_remaining_len = m.start_source - start;
-                                        let moved_len = m.len;
-                                        let second_remaining_len =
-                                            start + len - m.start_source - m.len;
-                                        mapped.push((m.start_dest, moved_len));
-                                        return vec![
-                                            (start, first_remaining_len),
-                                            (
-                                                start + first_remaining_len + moved_len,
-                                                second_remaining_len,
-                                            ),
-                                        ];
-                                    } else {
-                                        return vec![(start, len)];
-                                    }
-                                })
-                                .collect::<Vec<(usize, usize)>>();
-                        });
-                        mapped.append(&mut remaining);
-                        return mapped;
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        path: String::new(),
+        table: false,
+        lookups: Vec::new(),
+        inverse: None,
+        from: "seed".to_string(),
+        to: None,
+        seed_mode: SeedMode::Ranges,
+    };
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "--table" => options.table = true,
+            "--lookup" => {
+                options.lookups = value("--lookup")?
+                    .split(',')
+                    .map(|v| {
+                        v.trim()
+                            .parse()
+                            .map_err(|e| format!("Invalid value '{v}': {e}"))
                     })
-                    .collect::<Vec<(usize, usize)>>();
+                    .collect::<Result<Vec<usize>, String>>()?
             }
-            None => {
-                //print_mapping(&mapped_to, precedent);
-                break;
+            "--inverse" => {
+                options.inverse = Some(
+                    parse_window(value("--inverse")?)
+                        .ok_or("--inverse expects a value or a window like 40-50")?,
+                )
             }
+            "--from" => options.from = value("--from")?.clone(),
+            "--to" => options.to = Some(value("--to")?.clone()),
+            "--seeds" => {
+                options.seed_mode = match value("--seeds")?.as_str() {
+                    "single" => SeedMode::Single,
+                    "ranges" => SeedMode::Ranges,
+                    other => {
+                        return Err(format!(
+                            "Unknown seed mode '{other}', expected single or ranges"
+                        ))
+                    }
+                }
+            }
+            _ if options.path.is_empty() => options.path = arg.clone(),
+            _ => return Err(format!("Unexpected argument '{arg}'")),
         }
     }
-
-    mapped_to.sort();
-    return mapped_to[0].0;
+    if options.path.is_empty() {
+        return Err("Usage: aoc_5 <input> [--table] [--lookup <values>] [--inverse <window>] [--from <category>] [--to <category>] [--seeds single|ranges]".to_string());
+    }
+    Ok(options)
 }
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let path = &args[1];
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
 
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
+    let contents =
+        fs::read_to_string(&options.path).expect("Should have been able to read the file");
 
     let parse_result = MyParser::parse(Rule::file, &contents);
 
     match parse_result {
         Ok(mut result) => match analyze_file(&mut result) {
-            Ok((seeds, maps)) => {
-                let location = solve_seeding(seeds, maps);
-                println!("closest location is {location}");
+            Ok((numbers, maps)) => {
+                let seeds = interpret_seeds(&numbers, &options.seed_mode);
+                for problem in validate(&maps) {
+                    println!("almanac problem: {problem}");
+                }
+
+                let chain = match get_chain(&maps, &options.from, options.to.as_deref()) {
+                    Ok(chain) => chain,
+                    Err(e) => {
+                        println!("ERR:  {e}");
+                        return;
+                    }
+                };
+                let from = options.from.as_str();
+                let to = chain.last().map_or(from, |m| m.to.as_str());
+                let composed = compose_chain(&chain);
+                if options.table {
+                    composed.print(from, to);
+                }
+                for value in &options.lookups {
+                    println!("{from} {value} -> {to} {}", composed.apply(*value));
+                }
+                if let Some((start, end)) = options.inverse {
+                    println!("{to} {start}-{} comes from", end - 1);
+                    for (low, high) in inverse_chain(&chain, start, end) {
+                        println!("  {from} {low}-{}", high - 1);
+                    }
+                }
+
+                match get_chain(&maps, "seed", None) {
+                    Ok(seed_chain) => match solve_seeding(&seeds, &compose_chain(&seed_chain)) {
+                        Some(location) => println!("closest location is {location}"),
+                        None => println!("no seeds to map"),
+                    },
+                    Err(e) => println!("ERR:  {e}"),
+                }
             }
             Err(e) => println!("Error parsing file: {e}"),
         },
@@ -27,12 +27,15 @@ fn analyze_number_list(parsed: Pair<'_, Rule>) -> Result<Vec<u64>, &'static str>
         }
     }
 
-    // return Ok(list);
+    Ok(list)
+}
 
-    let base: u64 = 10;
-    return Ok(vec![list.iter().fold(0, |accumulator: u64, n: &u64| {
-        accumulator * base.pow(n.to_string().len().try_into().unwrap()) + n
-    })]);
+// reads the numbers as one with the spaces removed, None if that does not fit into u64
+fn kern(list: &[u64]) -> Option<u64> {
+    list.iter().try_fold(0, |accumulator: u64, n: &u64| {
+        let shift = 10u64.checked_pow(n.to_string().len() as u32)?;
+        accumulator.checked_mul(shift)?.checked_add(*n)
+    })
 }
 
 fn analyze_number_list_entry(parsed: Pair<'_, Rule>) -> Result<Vec<u64>, &'static str> {
@@ -52,8 +55,8 @@ fn analyze_number_list_entry(parsed: Pair<'_, Rule>) -> Result<Vec<u64>, &'stati
         }
     }
     match list {
-        Some(l) => return Ok(l),
-        None => return Err("Found no number list"),
+        Some(l) => Ok(l),
+        None => Err("Found no number list"),
     }
 }
 
@@ -80,38 +83,150 @@ fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Result<(Vec<u64>, Vec<u64>), &'
         }
     }
     match (times, distances) {
-        (Some(t), Some(d)) => return Ok((t, d)),
-        (Some(_t), None) => return Err("Could not parse  distances"),
-        (None, Some(_d)) => return Err("Could not parse times"),
-        (None, None) => return Err("Could not parse times and distances"),
+        (Some(t), Some(d)) => Ok((t, d)),
+        (Some(_t), None) => Err("Could not parse  distances"),
+        (None, Some(_d)) => Err("Could not parse times"),
+        (None, None) => Err("Could not parse times and distances"),
     }
 }
 
-fn restructure_races<'a>(times: &'a Vec<u64>, distances: &'a Vec<u64>) -> Vec<(&'a u64, &'a u64)> {
+fn restructure_races<'a>(times: &'a [u64], distances: &'a [u64]) -> Vec<(&'a u64, &'a u64)> {
     let games: Vec<(&u64, &u64)> = times.iter().zip(distances.iter()).collect();
-    return games;
+    games
+}
+
+// holding for h leaves t - h to travel at speed h, so the boat wins when (t - h) * h > d
+fn count_wins(time: u64, distance: u64) -> u64 {
+    let t = u128::from(time);
+    let d = u128::from(distance);
+    let beats = |h: u128| h <= t && (t - h) * h > d;
+
+    // t * t < 4 * d means the parabola never gets above the record
+    if t * t < 4 * d {
+        return 0;
+    }
+    let root = (t * t - 4 * d).isqrt();
+
+    // (t - root) / 2 is within one of the lowest winning hold time
+    let mut low = (t - root) / 2;
+    while low > 0 && beats(low - 1) {
+        low -= 1;
+    }
+    while low <= t / 2 && !beats(low) {
+        low += 1;
+    }
+    if low > t / 2 {
+        return 0;
+    }
+    // the winning window is symmetric around t / 2
+    let high = t - low;
+    (high - low + 1) as u64
+}
+
+// range of records leaving exactly `wins` winning hold times for a race of length time,
+// None as the upper end means every larger record works as well
+fn record_for_wins(time: u64, wins: u64) -> Option<(u128, Option<u128>)> {
+    let t = u128::from(time);
+    let best = (t / 2) * (t - t / 2);
+    if wins == 0 {
+        return Some((best, None));
+    }
+    // the winning window lo..=t-lo holds t - 2 * lo + 1 hold times and lo is at least 1
+    let k = u128::from(wins);
+    if k >= t || (t + 1 - k) % 2 != 0 {
+        return None;
+    }
+    let low = (t + 1 - k) / 2;
+    let lowest = (low - 1) * (t - low + 1);
+    let highest = low * (t - low) - 1;
+    Some((lowest, Some(highest)))
+}
+
+// shortest race allowing at least `wins` winning hold times against the record distance,
+// None if not even the longest race gets there
+fn min_time_for_wins(distance: u64, wins: u64) -> Option<u64> {
+    if wins == 0 {
+        return Some(0);
+    }
+    let mut high: u64 = 1;
+    while count_wins(high, distance) < wins {
+        if high == u64::MAX {
+            return None;
+        }
+        high = high.saturating_mul(2);
+    }
+    let mut low: u64 = 0;
+    // count_wins grows with the race time, so the first time reaching wins is in low+1..=high
+    while low + 1 < high {
+        let mid = low + (high - low) / 2;
+        if count_wins(mid, distance) >= wins {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Some(high)
 }
 
 fn count_win_possibilities(games: Vec<(&u64, &u64)>) -> Vec<u64> {
-    return games
+    games
         .iter()
-        .map(|&(time, distance)| {
-            let mut wins: u64 = 0;
-            for t0 in 1..*time {
-                if (time - t0) * t0 >= *distance {
-                    wins += 1;
+        .map(|&(time, distance)| count_wins(*time, *distance))
+        .collect()
+}
+
+fn parse_arg(args: &[String], index: usize, name: &str) -> Result<u64, String> {
+    args.get(index)
+        .ok_or(format!("Missing {name}"))?
+        .parse()
+        .map_err(|e| format!("Invalid {name}: {e}"))
+}
+
+// `record <time> <wins>` and `min-time <distance> <wins>` plan races instead of reading a sheet
+fn plan(args: &[String]) -> Result<bool, String> {
+    match args.get(1).map(String::as_str) {
+        Some("record") => {
+            let time = parse_arg(args, 2, "time")?;
+            let wins = parse_arg(args, 3, "wins")?;
+            match record_for_wins(time, wins) {
+                Some((lowest, Some(highest))) => {
+                    println!("Race of {time} has {wins} wins for records {lowest} to {highest}")
+                }
+                Some((lowest, None)) => {
+                    println!("Race of {time} has {wins} wins for records from {lowest} on")
                 }
+                None => println!("Race of {time} can not have exactly {wins} wins"),
             }
-
-            // t = t0 + d/t0
-            // d = (t-t0)t0
-            return wins;
-        })
-        .collect();
+            Ok(true)
+        }
+        Some("min-time") => {
+            let distance = parse_arg(args, 2, "distance")?;
+            let wins = parse_arg(args, 3, "wins")?;
+            match min_time_for_wins(distance, wins) {
+                Some(time) => println!(
+                    "Record {distance} needs a race of {time} for at least {wins} wins ({} wins)",
+                    count_wins(time, distance)
+                ),
+                None => println!(
+                    "Record {distance} gets no {wins} wins from any race time that fits into u64"
+                ),
+            }
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    match plan(&args) {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    }
     let path = &args[1];
 
     let contents = fs::read_to_string(path).expect("Should have been able to read the file");
@@ -121,11 +236,33 @@ fn main() {
     match parse_result {
         Ok(mut result) => match analyze_file(&mut result) {
             Ok((times, distances)) => {
+                if times.len() != distances.len() {
+                    println!(
+                        "{} times but {} distances, extra entries are ignored",
+                        times.len(),
+                        distances.len()
+                    );
+                }
                 let games = restructure_races(&times, &distances);
-                let wins = count_win_possibilities(games);
-                let score = wins.iter().fold(1, |accumulator: u64, w| accumulator * w);
+                let wins = count_win_possibilities(games.clone());
+                for (i, (&(time, distance), w)) in games.iter().zip(&wins).enumerate() {
+                    println!("Race {}: time {time}, record {distance}, {w} wins", i + 1);
+                }
+                match wins
+                    .iter()
+                    .try_fold(1u64, |product, w| product.checked_mul(*w))
+                {
+                    Some(score) => println!("Score is {score}"),
+                    None => println!("Score does not fit into u64"),
+                }
 
-                println!("Score is {score}")
+                match (kern(&times), kern(&distances)) {
+                    (Some(time), Some(distance)) => println!(
+                        "Kerned race: time {time}, record {distance}, {} wins",
+                        count_wins(time, distance)
+                    ),
+                    _ => println!("Kerned race does not fit into u64"),
+                }
             }
             Err(e) => println!("Error parsing file: {e}"),
         },
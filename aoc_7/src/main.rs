@@ -11,231 +11,246 @@ use pest_derive::Parser;
 #[grammar = "grammar.pest"]
 struct MyParser;
 
-enum Card {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    J,
-    Q,
-    K,
-    A,
-    None,
-}
+// a card is identified by its rank symbol, its strength depends on the rule set
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Card(char);
+
 struct Hand {
     cards: Vec<Card>,
     bid: u32,
 }
 
-impl Card {
-    pub fn from_str(str: &str) -> Card {
-        match str.chars().next().expect("could not parse Card") {
-            '2' => Card::Two,
-            '3' => Card::Three,
-            '4' => Card::Four,
-            '5' => Card::Five,
-            '6' => Card::Six,
-            '7' => Card::Seven,
-            '8' => Card::Eight,
-            '9' => Card::Nine,
-            'T' => Card::Ten,
-            'J' => Card::J,
-            'Q' => Card::Q,
-            'K' => Card::K,
-            'A' => Card::A,
-            _ => Card::None,
+// card order, wildcards and hand size of one variant of the game
+struct RuleSet {
+    name: String,
+    // weakest card first
+    order: Vec<Card>,
+    wildcards: Vec<Card>,
+    hand_size: Option<usize>,
+}
+
+impl RuleSet {
+    // ranks are given strongest first, as in "AKQJT98765432"
+    fn new(name: &str, ranks: &str, wildcards: &str) -> Result<RuleSet, String> {
+        let order: Vec<Card> = ranks.chars().rev().map(Card).collect();
+        for (i, c) in order.iter().enumerate() {
+            if order[i + 1..].contains(c) {
+                return Err(format!("Rank '{}' appears twice in '{ranks}'", c.0));
+            }
         }
-    }
-    fn as_value(&self) -> i32 {
-        match self {
-            Card::None => 0,
-            Card::Two => 2,
-            Card::Three => 3,
-            Card::Four => 4,
-            Card::Five => 5,
-            Card::Six => 6,
-            Card::Seven => 7,
-            Card::Eight => 8,
-            Card::Nine => 9,
-            Card::Ten => 10,
-            Card::J => 1,
-            Card::Q => 12,
-            Card::K => 13,
-            Card::A => 14,
+        let wildcards: Vec<Card> = wildcards.chars().filter(|c| *c != ',').map(Card).collect();
+        if let Some(c) = wildcards.iter().find(|c| !order.contains(c)) {
+            return Err(format!("Wildcard '{}' is not part of '{ranks}'", c.0));
         }
+        Ok(RuleSet {
+            name: name.to_string(),
+            order,
+            wildcards,
+            hand_size: None,
+        })
     }
-}
 
-impl PartialEq for Card {
-    fn eq(&self, other: &Card) -> bool {
-        self.as_value() == other.as_value()
+    fn standard() -> RuleSet {
+        RuleSet::new("standard", "AKQJT98765432", "").expect("valid standard ranks")
     }
-}
-impl PartialOrd for Card {
-    fn partial_cmp(&self, other: &Card) -> Option<Ordering> {
-        if *self == Card::None {
-            return Some(Ordering::Less);
-        } else if *other == Card::None {
-            return Some(Ordering::Greater);
+
+    // J is the weakest card but stands in for whatever makes the best hand
+    fn jokers() -> RuleSet {
+        RuleSet::new("jokers", "AKQT98765432J", "J").expect("valid joker ranks")
+    }
+
+    // 1 for the weakest card, 0 is left for cards outside of the deck
+    fn value(&self, card: &Card) -> usize {
+        self.order
+            .iter()
+            .position(|c| c == card)
+            .map_or(0, |i| i + 1)
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wildcards.contains(card)
+    }
+
+    fn check(&self, hand: &Hand, expected_size: usize) -> Result<(), String> {
+        let text: String = hand.cards.iter().map(|c| c.0).collect();
+        if let Some(c) = hand.cards.iter().find(|c| self.value(c) == 0) {
+            return Err(format!(
+                "Hand {text} has card '{}' outside of the deck",
+                c.0
+            ));
+        }
+        if hand.cards.len() != expected_size {
+            return Err(format!(
+                "Hand {text} has {} cards, expected {expected_size}",
+                hand.cards.len()
+            ));
         }
-        let self_value = self.as_value();
-        let other_value = other.as_value();
-        Some(self_value.cmp(&other_value))
+        Ok(())
     }
 }
-struct CardCount {
-    card: Card,
-    count: u32,
+
+// sizes of the groups of equal cards, largest first. Comparing these lexicographically
+// ranks five of a kind over four of a kind over full house and so on, and keeps working
+// for larger hands: six of a kind [6] beats [5, 1], [3, 3] beats three pair [2, 2, 2]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct HandType {
+    groups: Vec<usize>,
 }
 
-impl Hand {
-    fn get_hand_value(&self) -> u32 {
-        let mut accu: Vec<CardCount> = vec![
-            CardCount {
-                card: Card::Two,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Three,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Four,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Five,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Six,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Seven,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Eight,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Nine,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Ten,
-                count: 0,
-            },
-            CardCount {
-                card: Card::J,
-                count: 0,
-            },
-            CardCount {
-                card: Card::Q,
-                count: 0,
-            },
-            CardCount {
-                card: Card::K,
-                count: 0,
-            },
-            CardCount {
-                card: Card::A,
-                count: 0,
-            },
-            CardCount {
-                card: Card::None,
-                count: 0,
-            },
-        ];
-        for c in &self.cards {
-            if *c == Card::J {
-                for cc in accu.iter_mut() {
-                    cc.count += 1;
-                }
+impl HandType {
+    // wildcards join the largest group of equal cards, which always gives the best hand
+    fn classify(cards: &[Card], rules: &RuleSet) -> HandType {
+        let mut histogram = vec![0; rules.order.len() + 1];
+        let mut wild = 0;
+        for c in cards {
+            if rules.is_wild(c) {
+                wild += 1;
             } else {
-                match accu.iter_mut().find(|cc| cc.card.eq(c)) {
-                    Some(cc) => cc.count += 1,
-                    None => println!("Err: Lost cardcount!"),
-                };
+                histogram[rules.value(c)] += 1;
             }
         }
-        accu.sort_by(|a, b| {
-            return b.count.cmp(&a.count);
-        });
-        let jokers = match accu.iter_mut().find(|cc| cc.card.eq(&Card::J)) {
-            Some(cc) => cc.count,
-            None => 0,
-        };
-        match accu.get(0).expect("Err: Lost cardcount later!").count {
-            5 => return 6, // five of a kind
-            4 => return 5, // four of a kind
-            i if i == 3 => match accu.get(1).expect("Err: Lost cardcount later!").count {
-                j if j >= 1 => {
-                    if i + j - jokers >= 5 {
-                        return 4;
-                    } else {
-                        return 3;
-                    }
-                } // full house
-                _ => println!("unexpected count!"),
-            },
-            i if i == 2 => match accu.get(1).expect("Err: Lost cardcount later!").count {
-                j if j >= 1 => {
-                    if i + j - jokers >= 4 {
-                        return 2;
-                    } else {
-                        return 1;
-                    }
-                } // full house
-                _ => println!("unexpected count!"),
-            },
-            1 => return 0, // high card
-            _ => println!("unexpected count!3"),
+        let mut groups: Vec<usize> = histogram.into_iter().filter(|&n| n > 0).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        match groups.first_mut() {
+            Some(largest) => *largest += wild,
+            None => groups.push(wild),
         }
-        return 0;
+        HandType { groups }
     }
-}
 
-impl PartialEq for Hand {
-    fn eq(&self, other: &Hand) -> bool {
-        for (s, o) in self.cards.iter().zip(other.cards.iter()) {
-            if *s != *o {
-                return false;
-            }
+    fn name(&self) -> String {
+        const COUNTS: [&str; 10] = [
+            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        let word = |n: usize| COUNTS.get(n).map_or(n.to_string(), |w| w.to_string());
+        if self.groups == [3, 2] {
+            return "full house".to_string();
+        }
+        let mut parts: Vec<String> = Vec::new();
+        let mut i = 0;
+        while i < self.groups.len() && self.groups[i] > 1 {
+            let size = self.groups[i];
+            let count = self.groups[i..].iter().take_while(|&&g| g == size).count();
+            parts.push(match (size, count) {
+                (2, 1) => "one pair".to_string(),
+                (2, _) => format!("{} pair", word(count)),
+                (_, 1) => format!("{} of a kind", word(size)),
+                _ => format!("{} {} of a kind", word(count), word(size)),
+            });
+            i += count;
         }
-        return true;
+        if parts.is_empty() {
+            return "high card".to_string();
+        }
+        parts.join(" and ")
     }
 }
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Hand) -> Option<Ordering> {
-        // compare hand types (hand values)
-        match self.get_hand_value().cmp(&other.get_hand_value()) {
-            Ordering::Less => return Some(Ordering::Less),
-            Ordering::Greater => return Some(Ordering::Greater),
-            Ordering::Equal => {}
+
+// a hand with its type and card values under one rule set, computed once before sorting
+struct RankedHand<'a> {
+    hand: &'a Hand,
+    hand_type: HandType,
+    values: Vec<usize>,
+}
+
+impl<'a> RankedHand<'a> {
+    fn new(hand: &'a Hand, rules: &RuleSet) -> RankedHand<'a> {
+        RankedHand {
+            hand,
+            hand_type: HandType::classify(&hand.cards, rules),
+            values: hand.cards.iter().map(|c| rules.value(c)).collect(),
         }
+    }
 
-        // compare equal hand types (card values)
-        for (s, o) in self.cards.iter().zip(other.cards.iter()) {
-            match s.partial_cmp(o) {
-                Some(Ordering::Equal) => {}
-                Some(o) => return Some(o),
-                None => return None,
-            }
+    // the hand with every wildcard replaced by the card that gives it its type
+    fn best_hand(&self, rules: &RuleSet) -> String {
+        let mut counts = vec![0; rules.order.len() + 1];
+        for c in self.hand.cards.iter().filter(|c| !rules.is_wild(c)) {
+            counts[rules.value(c)] += 1;
         }
-        return Some(Ordering::Equal);
+        // the largest group, on ties the strongest card; the strongest regular card without any
+        let target = (1..counts.len())
+            .filter(|&v| counts[v] > 0)
+            .max_by_key(|&v| (counts[v], v))
+            .or_else(|| {
+                (1..counts.len())
+                    .rev()
+                    .find(|&v| !rules.is_wild(&rules.order[v - 1]))
+            })
+            .map(|v| rules.order[v - 1]);
+        self.hand
+            .cards
+            .iter()
+            .map(|c| match target {
+                Some(target) if rules.is_wild(c) => target.0,
+                _ => c.0,
+            })
+            .collect()
+    }
+
+    fn compare(&self, other: &RankedHand) -> Ordering {
+        self.hand_type
+            .cmp(&other.hand_type)
+            .then_with(|| self.values.cmp(&other.values))
     }
 }
-impl Eq for Hand {}
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        return self.partial_cmp(other).expect("Could not order Hand");
+
+fn rank_hands<'a>(hands: &'a [Hand], rules: &RuleSet) -> Result<Vec<RankedHand<'a>>, String> {
+    let expected_size = rules
+        .hand_size
+        .or(hands.first().map(|h| h.cards.len()))
+        .unwrap_or(0);
+    for hand in hands {
+        rules.check(hand, expected_size)?;
+    }
+    let mut ranked: Vec<RankedHand> = hands.iter().map(|h| RankedHand::new(h, rules)).collect();
+    ranked.sort_by(|a, b| a.compare(b));
+    Ok(ranked)
+}
+
+// the rank of a hand is its position in the sorted list, starting at 1
+fn winnings(rank: usize, hand: &RankedHand) -> u64 {
+    rank as u64 * u64::from(hand.hand.bid)
+}
+
+enum Format {
+    Table,
+    Csv,
+}
+
+fn print_report(ranked: &[RankedHand], rules: &RuleSet, format: &Format) {
+    match format {
+        Format::Table => {
+            println!("Ranking with {} rules:", rules.name);
+            println!(
+                "{:>5} {:<8} {:<28} {:<8} {:>6} {:>10}",
+                "Rank", "Hand", "Type", "Best", "Bid", "Winnings"
+            );
+        }
+        Format::Csv => {}
+    }
+    for (i, r) in ranked.iter().enumerate() {
+        let text: String = r.hand.cards.iter().map(|c| c.0).collect();
+        let best = r.best_hand(rules);
+        match format {
+            Format::Table => println!(
+                "{:>5} {:<8} {:<28} {:<8} {:>6} {:>10}",
+                i + 1,
+                text,
+                r.hand_type.name(),
+                best,
+                r.hand.bid,
+                winnings(i + 1, r)
+            ),
+            Format::Csv => println!(
+                "{},{},{text},{},{best},{},{}",
+                rules.name,
+                i + 1,
+                r.hand_type.name(),
+                r.hand.bid,
+                winnings(i + 1, r)
+            ),
+        }
     }
 }
 
@@ -244,28 +259,22 @@ fn analyze_line(parsed: Pair<'_, Rule>) -> Result<Hand, &'static str> {
     let mut bid: Option<u32> = None;
     for entry in parsed.into_inner() {
         match entry.as_rule() {
-            Rule::card => {
-                cards.push(Card::from_str(entry.as_str()));
+            Rule::cards => {
+                cards = entry.as_str().chars().map(Card).collect();
             }
             Rule::number => match entry.as_str().parse::<u32>() {
                 Ok(i) => bid = Some(i),
-                Err(e) => println!("Error parsing number {e}"),
+                Err(e) => eprintln!("Error parsing number {e}"),
             },
-            Rule::EOI => {
-                println!("  EOI {}", entry.as_str());
-            }
             _ => {
-                println!("UNEXPECTED PARSE(entry) {}", entry.as_str());
+                eprintln!("UNEXPECTED PARSE(entry) {}", entry.as_str());
             }
         }
     }
-    return match bid {
-        Some(b) => Ok(Hand {
-            cards: cards,
-            bid: b,
-        }),
+    match bid {
+        Some(b) => Ok(Hand { cards, bid: b }),
         None => Err("Could not parse hand, missing bid"),
-    };
+    }
 }
 
 fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Hand> {
@@ -275,22 +284,78 @@ fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Hand> {
         match line.as_rule() {
             Rule::line => match analyze_line(line) {
                 Ok(hand) => hands.push(hand),
-                Err(e) => print!("Error parsing line: {e}"),
+                Err(e) => eprintln!("Error parsing line: {e}"),
             },
-            Rule::EOI => {
-                println!("EOI {}", line.as_str());
-            }
+            Rule::EOI => {}
             _ => {
-                println!("UNEXPECTED PARSE(File) {}", line.as_str());
+                eprintln!("UNEXPECTED PARSE(File) {}", line.as_str());
             }
         }
     }
-    return hands;
+    hands
+}
+
+struct Options {
+    rule_sets: Vec<RuleSet>,
+    format: Format,
+}
+
+// without --ranks or --wild both presets are played, otherwise only the custom variant
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut format = Format::Table;
+    let mut ranks: Option<&str> = None;
+    let mut wildcards: Option<&str> = None;
+    let mut hand_size: Option<usize> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "--ranks" => ranks = Some(value("--ranks")?),
+            "--wild" => wildcards = Some(value("--wild")?),
+            "--hand-size" => {
+                hand_size = Some(
+                    value("--hand-size")?
+                        .parse()
+                        .map_err(|e| format!("Invalid hand size: {e}"))?,
+                )
+            }
+            "--format" => {
+                format = match value("--format")?.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    other => {
+                        return Err(format!("Unknown format '{other}', expected table or csv"))
+                    }
+                }
+            }
+            _ => return Err(format!("Unexpected argument '{arg}'")),
+        }
+    }
+    let mut rule_sets = if ranks.is_none() && wildcards.is_none() {
+        vec![RuleSet::standard(), RuleSet::jokers()]
+    } else {
+        vec![RuleSet::new(
+            "custom",
+            ranks.unwrap_or("AKQJT98765432"),
+            wildcards.unwrap_or(""),
+        )?]
+    };
+    for rules in &mut rule_sets {
+        rules.hand_size = hand_size;
+    }
+    Ok(Options { rule_sets, format })
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let path = &args[1];
+    let options = match parse_args(&args[2..]) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
 
     let contents = fs::read_to_string(path).expect("Should have been able to read the file");
 
@@ -298,20 +363,31 @@ fn main() {
 
     match parse_result {
         Ok(mut result) => {
-            let mut hands = analyze_file(&mut result);
-            hands.sort();
-            println!("Sorted:");
-            for h in &hands {
-                print!("C({}, {}):", h.bid, h.get_hand_value());
-                for c in &h.cards {
-                    print!("{},", c.as_value());
+            let hands = analyze_file(&mut result);
+            if let Format::Csv = options.format {
+                println!("rules,rank,hand,type,best,bid,winnings");
+            }
+            let mut sums: Vec<(&str, u64)> = Vec::new();
+            for rules in &options.rule_sets {
+                match rank_hands(&hands, rules) {
+                    Ok(ranked) => {
+                        print_report(&ranked, rules, &options.format);
+                        let sum = ranked
+                            .iter()
+                            .enumerate()
+                            .map(|(i, r)| winnings(i + 1, r))
+                            .sum();
+                        sums.push((&rules.name, sum));
+                    }
+                    Err(e) => eprintln!("ERR:  {e}"),
+                }
+            }
+            for (name, sum) in sums {
+                match options.format {
+                    Format::Table => println!("Sum with {name} rules is {sum}"),
+                    Format::Csv => eprintln!("Sum with {name} rules is {sum}"),
                 }
-                println!("");
             }
-            let sum = hands.iter().enumerate().fold(0, |accu, (i, h)| {
-                accu + u32::try_from(i + 1).unwrap() * h.bid
-            });
-            println!("Sum is {sum}");
         }
         Err(result) => {
             println!("ERR:  Could not parse file: {result}");
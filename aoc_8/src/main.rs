@@ -1,4 +1,9 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::env;
+use std::fmt::Write;
 use std::fs;
 
 use pest::iterators::Pair;
@@ -6,8 +11,6 @@ use pest::iterators::Pairs;
 use pest::Parser;
 use pest_derive::Parser;
 
-use gcd::Gcd;
-
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
 struct MyParser;
@@ -16,47 +19,72 @@ enum Direction {
     Left,
     Right,
 }
-struct Node {
-    ident: u32,
-    left: u32,
-    right: u32,
-    fast_travel: u32,
-}
-impl Clone for Node {
-    fn clone(&self) -> Self {
-        return Node {
-            ident: self.ident,
-            left: self.left,
-            right: self.right,
-            fast_travel: self.fast_travel,
-        };
+
+impl Direction {
+    fn index(&self) -> usize {
+        match self {
+            Direction::Left => 0,
+            Direction::Right => 1,
+        }
     }
 }
-impl Copy for Node {}
 
-fn ident_to_num(ident: &str) -> u32 {
-    let mut chars = ident.chars();
-    match (chars.next(), chars.next(), chars.next()) {
-        (Some(c0), Some(c1), Some(c2)) => {
-            return ((c0 as u32) << 16) + ((c1 as u32) << 8) + (c2 as u32);
+const UNDEFINED: usize = usize::MAX;
+
+// node names are interned into dense indices, left and right hold the index of each neighbor
+struct Network {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+
+impl Network {
+    fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            index: HashMap::new(),
+            left: Vec::new(),
+            right: Vec::new(),
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        self.names.push(name.to_string());
+        self.left.push(UNDEFINED);
+        self.right.push(UNDEFINED);
+        self.index.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    fn step(&self, node: usize, dir: &Direction) -> usize {
+        match dir {
+            Direction::Left => self.left[node],
+            Direction::Right => self.right[node],
         }
-        _ => println!("Error converting ident"),
     }
-    return 0;
 }
 
-fn analyze_node(parsed: Pair<'_, Rule>) -> Result<Node, &str> {
-    let mut ident: Option<u32> = None;
-    let mut left: Option<u32> = None;
-    let mut right: Option<u32> = None;
+fn analyze_node(parsed: Pair<'_, Rule>, network: &mut Network) -> Result<(), &'static str> {
+    let mut ident: Option<usize> = None;
+    let mut left: Option<usize> = None;
+    let mut right: Option<usize> = None;
     for node_entry in parsed.into_inner() {
         match node_entry.as_rule() {
-            Rule::ident => ident = Some(ident_to_num(node_entry.as_str())),
-            Rule::left => left = Some(ident_to_num(node_entry.as_str())),
-            Rule::right => right = Some(ident_to_num(node_entry.as_str())),
-            Rule::EOI => {
-                println!("    EOI {}", node_entry.as_str());
-            }
+            Rule::ident => ident = Some(network.intern(node_entry.as_str())),
+            Rule::left => left = Some(network.intern(node_entry.as_str())),
+            Rule::right => right = Some(network.intern(node_entry.as_str())),
             _ => {
                 println!("UNEXPECTED PARSE(node) {}", node_entry.as_str());
             }
@@ -64,14 +92,14 @@ fn analyze_node(parsed: Pair<'_, Rule>) -> Result<Node, &str> {
     }
     match (ident, left, right) {
         (Some(i), Some(l), Some(r)) => {
-            return Ok(Node {
-                ident: i,
-                left: l,
-                right: r,
-                fast_travel: i,
-            })
+            if network.left[i] != UNDEFINED {
+                return Err("Node is defined twice");
+            }
+            network.left[i] = l;
+            network.right[i] = r;
+            Ok(())
         }
-        _ => return Err("Error parsing node, not all components could be parsed"),
+        _ => Err("Error parsing node, not all components could be parsed"),
     }
 }
 
@@ -79,268 +107,539 @@ fn analyze_sequence(parsed: Pair<'_, Rule>) -> Vec<Direction> {
     let mut seq: Vec<Direction> = Vec::new();
     for dir in parsed.into_inner() {
         match dir.as_rule() {
-            Rule::direction => match dir.as_str().chars().nth(0) {
+            Rule::direction => match dir.as_str().chars().next() {
                 Some('L') => seq.push(Direction::Left),
                 Some('R') => seq.push(Direction::Right),
                 Some(c) => println!("Char not a direction: {c}"),
                 None => println!("Could not get first char of direction"),
             },
-            Rule::EOI => {
-                println!("    EOI {}", dir.as_str());
-            }
             _ => {
                 println!("UNEXPECTED PARSE(seq) {}", dir.as_str());
             }
         }
     }
-    return seq;
+    seq
 }
 
-fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Result<(Vec<Direction>, Vec<Node>), &'static str> {
+fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Result<(Vec<Direction>, Network), String> {
     let mut seq: Option<Vec<Direction>> = None;
-    let mut nodes: Vec<Node> = Vec::new();
+    let mut network = Network::new();
 
     let unwrapped = parsed.next().unwrap();
     for entry in unwrapped.into_inner() {
         match entry.as_rule() {
             Rule::sequence => seq = Some(analyze_sequence(entry)),
-            Rule::node => match analyze_node(entry) {
-                Ok(node) => nodes.push(node),
-                Err(e) => println!("Error parsing node {e}"),
-            },
-            Rule::EOI => {
-                println!("EOI {}", entry.as_str());
+            Rule::node => {
+                let text = entry.as_str().trim().to_string();
+                if let Err(e) = analyze_node(entry, &mut network) {
+                    return Err(format!("{e}: {text}"));
+                }
             }
+            Rule::EOI => {}
             _ => {
                 println!("UNEXPECTED PARSE(File) {}", entry.as_str());
             }
         }
     }
+    if let Some(i) = (0..network.len()).find(|&i| network.left[i] == UNDEFINED) {
+        return Err(format!(
+            "Node {} is referenced but never defined",
+            network.names[i]
+        ));
+    }
     match seq {
-        Some(s) => return Ok((s, nodes)),
-        None => return Err("Could not parse sequence"),
+        Some(s) => Ok((s, network)),
+        None => Err("Could not parse sequence".to_string()),
     }
 }
 
-fn transmute_maze(seq: Vec<Direction>, nodes: &mut Vec<Node>) {
-    let nodes_copy = nodes.to_vec();
+// for every node, the node reached after following the whole sequence once
+fn transmute_maze(seq: &[Direction], network: &Network) -> Vec<usize> {
+    let mut fast_travel: Vec<usize> = (0..network.len()).collect();
     for dir in seq {
-        for node in &mut *nodes {
-            let fast_travel = nodes_copy
-                .iter()
-                .find(|n| n.ident == node.fast_travel)
-                .expect("could not resolve pointer");
-            let referenced = match &dir {
-                Direction::Left => fast_travel.left,
-                Direction::Right => fast_travel.right,
-            };
-            node.fast_travel = referenced;
+        for node in fast_travel.iter_mut() {
+            *node = network.step(*node, dir);
         }
     }
+    fast_travel
 }
 
-fn traverse_maze(nodes: &Vec<Node>, steps_per_run: usize) -> usize {
-    let mut node: &Node = nodes
-        .iter()
-        .find(|n| n.ident == ident_to_num("AAA"))
-        .expect("could not find start");
-    let mut runs = 0;
-    loop {
-        node = nodes
-            .iter()
-            .find(|n| n.ident == node.fast_travel)
-            .expect("could not find start");
-        runs += 1;
-        if node.ident == ident_to_num("ZZZ") {
-            break;
+// node name pattern, '*' matches any run of characters and '?' a single one
+struct Pattern {
+    text: String,
+}
+
+impl Pattern {
+    fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
         }
     }
 
-    return runs * steps_per_run;
+    fn is_literal(&self) -> bool {
+        !self.text.contains(['*', '?'])
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let pattern: Vec<char> = self.text.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        // matched[j]: the pattern read so far matches the first j characters of name
+        let mut matched = vec![false; name.len() + 1];
+        matched[0] = true;
+        for p in pattern {
+            let mut next = vec![false; name.len() + 1];
+            for j in 0..=name.len() {
+                next[j] = match p {
+                    '*' => matched[j] || (j > 0 && next[j - 1]),
+                    '?' => j > 0 && matched[j - 1],
+                    c => j > 0 && matched[j - 1] && name[j - 1] == c,
+                };
+            }
+            matched = next;
+        }
+        matched[name.len()]
+    }
 }
 
-fn check_node_type(ident: u32, to_check: &str) -> bool {
-    let val = (ident ^ ident_to_num(to_check)) & 0xff == 0;
-    return val;
+impl Network {
+    fn select(&self, pattern: &Pattern) -> Vec<usize> {
+        if pattern.is_literal() {
+            return self.find(&pattern.text).into_iter().collect();
+        }
+        (0..self.len())
+            .filter(|&i| pattern.matches(&self.names[i]))
+            .collect()
+    }
 }
 
-fn to_ident(ident: u32) -> String {
-    return String::from_utf8(vec![
-        ((ident >> 16) & 0xff) as u8,
-        ((ident >> 8) & 0xff) as u8,
-        ((ident) & 0xff) as u8,
-    ])
-    .expect("could not convert back");
+// a walk from every node matching start until all walkers stand on nodes matching goal
+struct Query {
+    label: String,
+    start: Pattern,
+    goal: Pattern,
 }
 
-fn traverse_maze_ghost(nodes: &Vec<Node>, steps_per_run: usize) -> usize {
-    let nodes_solved: Vec<(u32, usize, bool)> = nodes
-        .iter()
-        .map(|node| {
+struct Options {
+    path: String,
+    queries: Vec<Query>,
+    dot: Option<String>,
+    fast_travel: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut path = None;
+    let mut start = None;
+    let mut goal = None;
+    let mut dot = None;
+    let mut fast_travel = false;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| iter.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "--start" => start = Some(Pattern::new(value("--start")?)),
+            "--goal" => goal = Some(Pattern::new(value("--goal")?)),
+            "--dot" => dot = Some(value("--dot")?.clone()),
+            "--fast-travel" => fast_travel = true,
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{arg}'")),
+        }
+    }
+    let queries = match (start, goal) {
+        (Some(start), Some(goal)) => vec![Query {
+            label: format!("{} -> {}", start.text, goal.text),
+            start,
+            goal,
+        }],
+        (None, None) => vec![
+            Query {
+                label: "steps".to_string(),
+                start: Pattern::new("AAA"),
+                goal: Pattern::new("ZZZ"),
+            },
+            Query {
+                label: "ghost steps".to_string(),
+                start: Pattern::new("*A"),
+                goal: Pattern::new("*Z"),
+            },
+        ],
+        _ => return Err("--start and --goal have to be given together".to_string()),
+    };
+    Ok(Options {
+        path: path.ok_or(
+            "Usage: aoc_8 <input> [--start <pattern> --goal <pattern>] [--dot <file> [--fast-travel]]",
+        )?,
+        queries,
+        dot,
+        fast_travel,
+    })
+}
+
+// steps at which one walker stands on a goal: Exact is a single step before its loop,
+// Periodic repeats every period steps from offset on
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GoalTimes {
+    Exact(u128),
+    Periodic { offset: u128, period: u128 },
+}
+
+impl GoalTimes {
+    fn first(&self) -> u128 {
+        match self {
+            GoalTimes::Exact(t) => *t,
+            GoalTimes::Periodic { offset, .. } => *offset,
+        }
+    }
+
+    // steps at which both walkers stand on a goal
+    fn intersect(&self, other: &GoalTimes) -> Option<GoalTimes> {
+        match (*self, *other) {
+            (GoalTimes::Exact(a), GoalTimes::Exact(b)) => (a == b).then_some(GoalTimes::Exact(a)),
+            (GoalTimes::Exact(a), GoalTimes::Periodic { offset, period })
+            | (GoalTimes::Periodic { offset, period }, GoalTimes::Exact(a)) => {
+                (a >= offset && (a - offset).is_multiple_of(period)).then_some(GoalTimes::Exact(a))
+            }
             (
-                node.ident,
-                nodes
-                    .iter()
-                    .enumerate()
-                    .find_map(|(i, n)| {
-                        if n.ident == node.fast_travel {
-                            Some(i)
-                        } else {
-                            None
-                        }
-                    })
-                    .expect("could not find start"),
-                check_node_type(node.ident, "ZZZ"),
-            )
-        })
-        .collect();
-    let mut cur_nodes: Vec<(u32, usize, usize)> = nodes
-        .iter()
-        .enumerate()
-        .filter_map(|(i, n)| {
-            if check_node_type(n.ident, "AAA") {
-                Some((n.ident, i, i))
-            } else {
-                None
+                GoalTimes::Periodic {
+                    offset: o1,
+                    period: p1,
+                },
+                GoalTimes::Periodic {
+                    offset: o2,
+                    period: p2,
+                },
+            ) => {
+                let (x, period) = crt(o1, p1, o2, p2)?;
+                // lift the solution to the first step where both walkers are in their loops
+                let bound = o1.max(o2);
+                let offset = if x >= bound {
+                    x
+                } else {
+                    x + (bound - x).div_ceil(period) * period
+                };
+                Some(GoalTimes::Periodic { offset, period })
             }
-        })
-        .collect();
-    /*println!("found {} starts", cur_nodes.len());
-    let ends = nodes_solved.iter().filter(|(_ident, _refer, goal)| *goal);
-    for (ident, _refer, goal) in ends {
-        println!("Goal: {}({})", to_ident(*ident), *goal);
-    }*/
-    //println!("found {} ends", ends.count());
-
-    let mut nodes_history: Vec<(Vec<(usize, bool)>, usize)> = cur_nodes
-        .iter()
-        .map(|(_ident, _start_refer, _refer)| (vec![(*_start_refer, false)], 0))
-        .collect();
+        }
+    }
+}
 
-    let mut runs = 0;
-    let mut finished = false;
-    while !finished {
-        /*if runs % 10000000 == 0 {
-            println!("Round {}Mrd/13334", runs / 1000000000);
-        }*/
+// solves t = a mod m and t = b mod n, also for moduli that are not coprime
+fn crt(a: u128, m: u128, b: u128, n: u128) -> Option<(u128, u128)> {
+    let (g, p, _) = extended_gcd(m as i128, n as i128);
+    let g = g as u128;
+    let (a, b) = (a % m, b % n);
+    let diff = (b as i128 - a as i128).rem_euclid(n as i128) as u128;
+    if !diff.is_multiple_of(g) {
+        return None;
+    }
+    let lcm = m / g * n;
+    let n_g = (n / g) as i128;
+    // k = diff / g * p mod n / g, then t = a + k * m
+    let k = ((diff / g) as i128 % n_g * p.rem_euclid(n_g)).rem_euclid(n_g) as u128;
+    Some(((a + k * m) % lcm, lcm))
+}
 
-        finished = true;
-        for (i, (_start_ident, _start_index, node_index)) in cur_nodes.iter_mut().enumerate() {
-            let referenced = nodes_solved.get(*node_index).expect("could not find node");
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - (a / b) * y)
+}
 
-            let my_nodes_history = nodes_history.get_mut(i).expect("could not access history");
-            match my_nodes_history
-                .0
-                .iter()
-                .enumerate()
-                .find(|(_i, &(j, _goal))| j == referenced.1)
-            {
-                Some((i, (_j, _goal))) => {
-                    if my_nodes_history.1 == 0 {
-                        my_nodes_history.1 = my_nodes_history.0.len() - i;
-                        /*println!(
-                            "Found circle for {} with length {}",
-                            to_ident(*_start_ident),
-                            my_nodes_history.1
-                        );
-                        for (h, _goal) in &my_nodes_history.0 {
-                            print!(
-                                "{} ",
-                                to_ident(nodes_solved.get(*h).expect("could not solve pointer").0)
-                            );
-                        }
-                        println!(
-                            "{}",
-                            to_ident(
-                                nodes_solved
-                                    .get(referenced.1)
-                                    .expect("could not solve pointer")
-                                    .0
-                            )
-                        );*/
+// the nodes one walker visits until its (node, position in sequence) state repeats,
+// from loop_start on the visits repeat forever
+struct Walk {
+    nodes: Vec<usize>,
+    loop_start: usize,
+}
+
+impl Walk {
+    fn new(network: &Network, seq: &[Direction], start: usize) -> Self {
+        let mut seen_at: Vec<usize> = vec![UNDEFINED; network.len() * seq.len()];
+        let mut nodes: Vec<usize> = Vec::new();
+        let mut node = start;
+        loop {
+            let step = nodes.len();
+            let state = node * seq.len() + step % seq.len();
+            if seen_at[state] != UNDEFINED {
+                return Self {
+                    nodes,
+                    loop_start: seen_at[state],
+                };
+            }
+            seen_at[state] = step;
+            nodes.push(node);
+            node = network.step(node, &seq[step % seq.len()]);
+        }
+    }
+
+    fn on_goal(&self, step: u128, is_goal: &[bool]) -> bool {
+        let (len, loop_start) = (self.nodes.len() as u128, self.loop_start as u128);
+        let t = if step < len {
+            step
+        } else {
+            loop_start + (step - loop_start) % (len - loop_start)
+        };
+        is_goal[self.nodes[t as usize]]
+    }
+
+    fn goal_times(&self, is_goal: &[bool]) -> Vec<GoalTimes> {
+        let period = (self.nodes.len() - self.loop_start) as u128;
+        (0..self.nodes.len())
+            .filter(|&t| is_goal[self.nodes[t]])
+            .map(|t| {
+                if t < self.loop_start {
+                    GoalTimes::Exact(t as u128)
+                } else {
+                    GoalTimes::Periodic {
+                        offset: t as u128,
+                        period,
                     }
                 }
-                None => {
-                    my_nodes_history.0.push((
-                        referenced.1,
-                        nodes_solved
-                            .get(referenced.1)
-                            .expect("could not find node")
-                            .2,
-                    ));
-                }
+            })
+            .collect()
+    }
+
+    // (node, direction index) of every edge taken inside the loop
+    fn cycle_edges(&self, seq: &[Direction]) -> HashSet<(usize, usize)> {
+        (self.loop_start..self.nodes.len())
+            .map(|t| (self.nodes[t], seq[t % seq.len()].index()))
+            .collect()
+    }
+}
+
+// drops exact times the brute force already ruled out and keeps one congruence per
+// residue and period, the one with the smallest offset covers all others
+fn reduce(times: impl IntoIterator<Item = GoalTimes>, ruled_out: u128) -> Vec<GoalTimes> {
+    let mut exact: BTreeSet<u128> = BTreeSet::new();
+    let mut periodic: BTreeMap<(u128, u128), u128> = BTreeMap::new();
+    // later walkers may rule out any of these, so none of them can be dropped for being late
+    for time in times {
+        match time {
+            GoalTimes::Exact(t) if t > ruled_out => {
+                exact.insert(t);
             }
-            *node_index = referenced.1;
-
-            if !nodes_solved
-                .get(referenced.1)
-                .expect("could not find node")
-                .2
-            {
-                finished = false;
+            GoalTimes::Exact(_) => {}
+            GoalTimes::Periodic { offset, period } => {
+                let first = periodic.entry((offset % period, period)).or_insert(offset);
+                *first = (*first).min(offset);
             }
         }
-        if nodes_history.iter().filter(|(_h, len)| *len == 0).count() == 0 {
-            break;
+    }
+    exact
+        .into_iter()
+        .map(GoalTimes::Exact)
+        .chain(
+            periodic
+                .into_iter()
+                .map(|((_, period), offset)| GoalTimes::Periodic { offset, period }),
+        )
+        .collect()
+}
+
+// first step at which every walker stands on a goal, small answers are found by walking
+// and the rest by combining the walkers via CRT
+fn traverse_maze_ghost(
+    network: &Network,
+    seq: &[Direction],
+    starts: &[usize],
+    is_goal: &[bool],
+) -> Option<u128> {
+    if let Some(steps) = brute_force(network, seq, starts, is_goal, BRUTE_FORCE_LIMIT) {
+        return Some(steps as u128);
+    }
+    let ruled_out = BRUTE_FORCE_LIMIT as u128;
+    let mut combined: Vec<GoalTimes> = vec![GoalTimes::Periodic {
+        offset: 0,
+        period: 1,
+    }];
+    for &start in starts {
+        let walk = Walk::new(network, seq, start);
+        let times = reduce(walk.goal_times(is_goal), ruled_out);
+        let next: Vec<GoalTimes> = combined
+            .iter()
+            .flat_map(|c| match c {
+                // an exact time only needs a lookup instead of pairing it with every time
+                GoalTimes::Exact(t) => walk
+                    .on_goal(*t, is_goal)
+                    .then_some(*c)
+                    .into_iter()
+                    .collect::<Vec<GoalTimes>>(),
+                GoalTimes::Periodic { .. } => times.iter().filter_map(|t| c.intersect(t)).collect(),
+            })
+            .collect();
+        combined = reduce(next, ruled_out);
+        if combined.is_empty() {
+            return None;
         }
-        runs += 1;
     }
-    /*println!("Runs: {runs}");
+    combined.iter().map(GoalTimes::first).min()
+}
 
-    print!("Goals per start loop: ");
-    nodes_history
-        .iter()
-        .map(|(h, _len)| h.iter().filter(|(_refer, goal)| *goal).count())
-        .for_each(|count| println!(" {count}"));
-    println!("");*/
+// steps all walkers together, only feasible for small answers
+fn brute_force(
+    network: &Network,
+    seq: &[Direction],
+    starts: &[usize],
+    is_goal: &[bool],
+    limit: usize,
+) -> Option<usize> {
+    let mut nodes = starts.to_vec();
+    for step in 0..=limit {
+        if nodes.iter().all(|&n| is_goal[n]) {
+            return Some(step);
+        }
+        for node in nodes.iter_mut() {
+            *node = network.step(*node, &seq[step % seq.len()]);
+        }
+    }
+    None
+}
 
-    let lcm_val = nodes_history
-        .iter()
-        .map(|(h, len)| {
-            let index = h
+const BRUTE_FORCE_LIMIT: usize = 1_000_000;
+
+const CYCLE_COLORS: [&str; 8] = [
+    "red",
+    "blue",
+    "darkgreen",
+    "orange",
+    "purple",
+    "brown",
+    "deeppink",
+    "teal",
+];
+
+// L/R edges are merged when they lead to the same node, edges on a walker's loop take
+// the walker's color, start nodes are green, goal nodes red and nodes that are both gold
+fn export_dot(
+    network: &Network,
+    seq: &[Direction],
+    queries: &[Query],
+    fast_travel: bool,
+) -> String {
+    let mut is_start = vec![false; network.len()];
+    let mut is_goal = vec![false; network.len()];
+    let mut cycles: Vec<HashSet<(usize, usize)>> = Vec::new();
+    for query in queries {
+        for start in network.select(&query.start) {
+            is_start[start] = true;
+            cycles.push(Walk::new(network, seq, start).cycle_edges(seq));
+        }
+        for (node, name) in network.names.iter().enumerate() {
+            is_goal[node] |= query.goal.matches(name);
+        }
+    }
+
+    let mut dot = String::from("digraph network {\n    node [shape=ellipse];\n");
+    for (node, name) in network.names.iter().enumerate() {
+        let fill = match (is_start[node], is_goal[node]) {
+            (true, true) => "gold",
+            (true, false) => "palegreen",
+            (false, true) => "lightcoral",
+            (false, false) => continue,
+        };
+        writeln!(dot, "    \"{name}\" [style=filled, fillcolor={fill}];").unwrap();
+    }
+    for node in 0..network.len() {
+        let targets = [network.left[node], network.right[node]];
+        let labels: Vec<(usize, &str)> = if targets[0] == targets[1] {
+            vec![(targets[0], "LR")]
+        } else {
+            vec![(targets[0], "L"), (targets[1], "R")]
+        };
+        for (target, label) in labels {
+            let colors: Vec<&str> = cycles
                 .iter()
                 .enumerate()
-                .filter_map(|(i, (_refer, goal))| if *goal { Some(i) } else { None })
-                .next()
-                .expect("expected a goal to exist");
-            (index + len - h.len(), len, h.len() - len)
-        })
-        .fold(1, |accu, (index, len, skip)| {
-            //println!("Goal at {skip}+{index}/{len}");
-            return lcm(accu, *len);
-        });
-    //println!("Runs: {lcm_val}");
-    runs = lcm_val;
-
-    return runs * steps_per_run;
+                .filter(|(_, cycle)| {
+                    label
+                        .chars()
+                        .any(|c| cycle.contains(&(node, usize::from(c == 'R'))))
+                })
+                .map(|(i, _)| CYCLE_COLORS[i % CYCLE_COLORS.len()])
+                .collect();
+            let style = if colors.is_empty() {
+                String::new()
+            } else {
+                format!(", color=\"{}\", penwidth=2", colors.join(":"))
+            };
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{label}\"{style}];",
+                network.names[node], network.names[target]
+            )
+            .unwrap();
+        }
+    }
+    if fast_travel {
+        for (node, target) in transmute_maze(seq, network).into_iter().enumerate() {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"run\", style=dashed, color=gray];",
+                network.names[node], network.names[target]
+            )
+            .unwrap();
+        }
+    }
+    dot.push_str("}\n");
+    dot
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    return a * b / a.gcd(b);
+fn run_query(network: &Network, seq: &[Direction], query: &Query) {
+    let starts = network.select(&query.start);
+    if starts.is_empty() {
+        println!(
+            "no nodes match {}, skipping {}",
+            query.start.text, query.label
+        );
+        return;
+    }
+    let is_goal: Vec<bool> = network
+        .names
+        .iter()
+        .map(|n| query.goal.matches(n))
+        .collect();
+    let steps = traverse_maze_ghost(network, seq, &starts, &is_goal);
+    match steps {
+        Some(steps) => println!("final count of {} is {steps}", query.label),
+        None => println!(
+            "{} never reaches {} on all walkers at once",
+            query.start.text, query.goal.text
+        ),
+    }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let path = &args[1];
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
 
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
+    let contents =
+        fs::read_to_string(&options.path).expect("Should have been able to read the file");
 
     let parse_result = MyParser::parse(Rule::file, &contents);
 
     match parse_result {
-        Ok(mut result) => {
-            let maze = analyze_file(&mut result);
-            match maze {
-                Ok((seq, mut nodes)) => {
-                    let steps_per_run = seq.len();
-                    transmute_maze(seq, &mut nodes);
-                    let final_steps = 0; //traverse_maze(&nodes, steps_per_run);
-                    let final_steps_ghost = traverse_maze_ghost(&nodes, steps_per_run);
-                    println!("final count of steps is {final_steps}, {final_steps_ghost}");
+        Ok(mut result) => match analyze_file(&mut result) {
+            Ok((seq, network)) => {
+                for query in &options.queries {
+                    run_query(&network, &seq, query);
+                }
+                if let Some(file) = &options.dot {
+                    let dot = export_dot(&network, &seq, &options.queries, options.fast_travel);
+                    match fs::write(file, dot) {
+                        Ok(()) => println!("network written to {file}"),
+                        Err(e) => println!("ERR:  Could not write {file}: {e}"),
+                    }
                 }
-                Err(e) => println!("Error parsing maze: {e}"),
             }
-        }
+            Err(e) => println!("Error parsing maze: {e}"),
+        },
         Err(result) => {
             println!("ERR:  Could not parse file: {result}");
         }
@@ -10,14 +10,96 @@ use pest_derive::Parser;
 #[grammar = "grammar.pest"]
 struct MyParser;
 
+// the minimal-degree polynomial through the sequence, stored as the Newton forward
+// differences at position 0: f(x) = sum over k of binomial(x, k) * differences[k]
 struct Report {
-    sequence: Vec<i32>,
-    deductions: Vec<Vec<i32>>,
-    next_val: i32,
+    sequence: Vec<i128>,
+    differences: Vec<i128>,
+}
+
+impl Report {
+    fn new(sequence: Vec<i128>) -> Self {
+        let mut differences: Vec<i128> = Vec::new();
+        let mut row = sequence.clone();
+        while !row.is_empty() {
+            differences.push(row[0]);
+            row = row.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        }
+        // trailing zero differences do not contribute to the polynomial
+        while differences.last() == Some(&0) {
+            differences.pop();
+        }
+        Self {
+            sequence,
+            differences,
+        }
+    }
+
+    fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    // the polynomial at position x, negative positions lie before the first entry
+    fn value_at(&self, x: i128) -> Option<i128> {
+        let mut value: i128 = 0;
+        let mut binomial: i128 = 1;
+        for (k, &difference) in self.differences.iter().enumerate() {
+            value = value.checked_add(binomial.checked_mul(difference)?)?;
+            // binomial(x, k + 1) = binomial(x, k) * (x - k) / (k + 1), always divisible
+            let k = k as i128;
+            binomial = binomial.checked_mul(x - k)? / (k + 1);
+        }
+        Some(value)
+    }
+
+    fn forward(&self, steps: u32) -> Option<i128> {
+        self.value_at(self.sequence.len() as i128 - 1 + i128::from(steps))
+    }
+
+    fn backward(&self, steps: u32) -> Option<i128> {
+        self.value_at(-i128::from(steps))
+    }
+}
+
+struct Options {
+    path: String,
+    forward: u32,
+    backward: u32,
+    trace: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut path = None;
+    let mut forward = 1;
+    let mut backward = 1;
+    let mut trace = false;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut steps = |name: &str| -> Result<u32, String> {
+            iter.next()
+                .ok_or(format!("Missing value for {name}"))?
+                .parse()
+                .map_err(|e| format!("Invalid step count for {name}: {e}"))
+        };
+        match arg.as_str() {
+            "--forward" => forward = steps("--forward")?,
+            "--backward" => backward = steps("--backward")?,
+            "--trace" => trace = true,
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{arg}'")),
+        }
+    }
+    Ok(Options {
+        path: path
+            .ok_or("Usage: aoc_9 <input> [--forward <steps>] [--backward <steps>] [--trace]")?,
+        forward,
+        backward,
+        trace,
+    })
 }
 
 fn analyze_report(parsed: Pair<'_, Rule>) -> Report {
-    let mut sequence: Vec<i32> = Vec::new();
+    let mut sequence: Vec<i128> = Vec::new();
     for entry in parsed.into_inner() {
         match entry.as_rule() {
             Rule::entry => {
@@ -28,19 +110,12 @@ fn analyze_report(parsed: Pair<'_, Rule>) -> Report {
                         .expect("could not parse sequence entry"),
                 );
             }
-            Rule::EOI => {
-                println!("EOI {}", entry.as_str());
-            }
             _ => {
                 println!("UNEXPECTED PARSE(report) {}", entry.as_str());
             }
         }
     }
-    return Report {
-        sequence: sequence,
-        deductions: Vec::new(),
-        next_val: 0,
-    };
+    Report::new(sequence)
 }
 
 fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Report> {
@@ -49,175 +124,78 @@ fn analyze_file(parsed: &mut Pairs<'_, Rule>) -> Vec<Report> {
     for entry in unwrapped.into_inner() {
         match entry.as_rule() {
             Rule::report => reports.push(analyze_report(entry)),
-            Rule::EOI => {
-                println!("EOI {}", entry.as_str());
-            }
+            Rule::EOI => {}
             _ => {
                 println!("UNEXPECTED PARSE(File) {}", entry.as_str());
             }
         }
     }
-    return reports;
-}
-
-fn only_zeroes(vec: &Vec<i32>) -> bool {
-    return vec.iter().filter(|&item| *item != 0).count() == 0;
-}
-
-fn find_deductions(reports: &mut Vec<Report>) {
-    for report in reports.iter_mut() {
-        if (report.deductions.len() == 0 && only_zeroes(&report.sequence))
-            || (report.deductions.len() != 0
-                && only_zeroes(
-                    report
-                        .deductions
-                        .last()
-                        .expect("could not access last deduction1"),
-                ))
-        {
-        } else {
-            loop {
-                let last_deduction = if report.deductions.len() == 0 {
-                    &report.sequence
-                } else {
-                    report
-                        .deductions
-                        .last()
-                        .expect("could not access last deduction2")
-                };
-                let new_deduction = last_deduction
-                    .iter()
-                    .zip(last_deduction.iter().skip(1))
-                    .map(|(&a, &b)| b - a)
-                    .collect();
-                let is_finished = only_zeroes(&new_deduction);
-                report.deductions.push(new_deduction);
-                if is_finished {
-                    break;
-                }
-            }
-        }
-    }
+    reports
 }
 
-fn _extrapolate_reports(reports: &mut Vec<Report>) {
-    for report in reports.iter_mut() {
-        if report.deductions.len() == 0 {
-            report.next_val = *report
-                .sequence
-                .last()
-                .expect("could not access last of sequence");
-            report.sequence.push(report.next_val);
-        } else {
-            report.deductions.reverse();
-            let mut last_item = 0;
-            report
-                .deductions
-                .get_mut(0)
-                .expect("could not slice deductions(0)")
-                .push(0);
-            for deduction in report
-                .deductions
-                .get_mut(1..)
-                .expect("Could not slice deductions")
-                .iter_mut()
-            {
-                last_item = deduction
-                    .last()
-                    .expect("could not access last item of deduction")
-                    + last_item;
-                deduction.push(last_item);
-            }
-            last_item = report
-                .sequence
-                .last()
-                .expect("could not access last item of deduction")
-                + last_item;
-            report.next_val = last_item;
-            report.sequence.push(report.next_val);
-        }
-    }
-}
-fn extrapolate_reports_front(reports: &mut Vec<Report>) {
-    for report in reports.iter_mut() {
-        if report.deductions.len() == 0 {
-            report.next_val = *report
-                .sequence
-                .first()
-                .expect("could not access first of sequence");
-            report.sequence.insert(0, report.next_val);
-        } else {
-            report.deductions.reverse();
-            let mut last_item = 0;
-            report
-                .deductions
-                .get_mut(0)
-                .expect("could not slice deductions(0)")
-                .insert(0, 0);
-            for deduction in report
-                .deductions
-                .get_mut(1..)
-                .expect("Could not slice deductions")
-                .iter_mut()
-            {
-                last_item = deduction
-                    .first()
-                    .expect("could not access first item of deduction")
-                    - last_item;
-                deduction.insert(0, last_item);
-            }
-            last_item = report
-                .sequence
-                .first()
-                .expect("could not access last item of deduction")
-                - last_item;
-            report.next_val = last_item;
-            report.sequence.insert(0, report.next_val);
-        }
-    }
+fn sum_extrapolations(values: &[Option<i128>]) -> Option<i128> {
+    values
+        .iter()
+        .try_fold(0i128, |accu, value| accu.checked_add((*value)?))
 }
 
-fn sum_extrapolations(reports: &Vec<Report>) -> i32 {
-    return reports.iter().fold(0, |accu, rep| {
-        //println!("Summing: {}", rep.next_val);
-        accu + rep.next_val
-    });
+fn show(value: Option<i128>) -> String {
+    value.map_or("overflow".to_string(), |v| v.to_string())
 }
 
-fn _print_report(reports: &Vec<Report>) {
-    for rep in reports {
-        print!("Report:");
-        for seq in &rep.sequence {
-            print!(" {seq}");
-        }
-        println!("");
-        for (i, ded) in rep.deductions.iter().enumerate() {
-            print!("Deduction{i}:");
-            for j in ded {
-                print!(" {}", *j);
-            }
-            println!("");
-        }
-    }
-}
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let path = &args[1];
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("ERR:  {e}");
+            return;
+        }
+    };
 
-    let contents = fs::read_to_string(path).expect("Should have been able to read the file");
+    let contents =
+        fs::read_to_string(&options.path).expect("Should have been able to read the file");
 
     let parse_result = MyParser::parse(Rule::file, &contents);
 
     match parse_result {
         Ok(mut result) => {
-            let mut reports = analyze_file(&mut result);
-            find_deductions(&mut reports);
-            //_extrapolate_reports(&mut reports);
-            extrapolate_reports_front(&mut reports);
-            //_print_report(&reports);
-
-            let sum = sum_extrapolations(&reports);
-            println!("Sum is {sum}");
+            let reports = analyze_file(&mut result);
+            let forward: Vec<Option<i128>> = reports
+                .iter()
+                .map(|report| report.forward(options.forward))
+                .collect();
+            let backward: Vec<Option<i128>> = reports
+                .iter()
+                .map(|report| report.backward(options.backward))
+                .collect();
+
+            if options.trace {
+                for (i, report) in reports.iter().enumerate() {
+                    let degree = report
+                        .degree()
+                        .map_or("zero".to_string(), |d| d.to_string());
+                    println!(
+                        "Report {}: degree {degree}, -{}: {}, +{}: {}",
+                        i + 1,
+                        options.backward,
+                        show(backward[i]),
+                        options.forward,
+                        show(forward[i])
+                    );
+                }
+            }
+
+            println!(
+                "Forward sum (+{}) is {}",
+                options.forward,
+                show(sum_extrapolations(&forward))
+            );
+            println!(
+                "Backward sum (-{}) is {}",
+                options.backward,
+                show(sum_extrapolations(&backward))
+            );
         }
         Err(result) => {
             println!("ERR:  Could not parse file: {result}");